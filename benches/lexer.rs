@@ -0,0 +1,57 @@
+//! Lexer throughput on generated inputs of increasing size
+//!
+//! Run with `cargo bench --bench lexer`. Throughput should stay roughly flat
+//! as the input grows, since lexing is linear in the size of the input.
+
+#![allow(clippy::needless_return)]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use junkie::Lexer;
+
+const SNIPPET: &str = "let fib = fn(n) {
+    if (n < 2) { return n; }
+    return fib(n - 1) + fib(n - 2);
+};
+let result = fib(25) * 3 != 10 == !false;
+";
+
+const SIZES: [usize; 4] = [10_000, 100_000, 1_000_000, 4_000_000];
+
+/// Minimum time spent measuring each size
+const BUDGET: Duration = Duration::from_millis(500);
+
+fn lex_all(input: &str) -> usize {
+    return Lexer::new(input).count();
+}
+
+fn main() {
+    println!(
+        "{:>12} {:>10} {:>8} {:>12} {:>10}",
+        "bytes", "tokens", "runs", "per run", "MB/s"
+    );
+
+    for size in SIZES {
+        let input = SNIPPET.repeat(size / SNIPPET.len() + 1);
+
+        let mut runs = 0;
+        let mut tokens = 0;
+        let started = Instant::now();
+        while runs == 0 || started.elapsed() < BUDGET {
+            tokens = black_box(lex_all(black_box(&input)));
+            runs += 1;
+        }
+
+        let per_run = started.elapsed() / runs;
+        let throughput = input.len() as f64 / per_run.as_secs_f64() / 1_000_000.0;
+        println!(
+            "{:>12} {:>10} {:>8} {:>12.2?} {:>10.1}",
+            input.len(),
+            tokens,
+            runs,
+            per_run,
+            throughput
+        );
+    }
+}
@@ -1,126 +1,651 @@
-use crate::token::Token;
+pub mod printer;
 
-pub trait Node {
-    fn token_literal(&self) -> &String {
-        todo!()
-    }
-    fn to_string(&self) -> &String;
+use std::fmt;
+
+use crate::token::{OwnedToken, Span};
+
+/// Every AST node prints back as canonical Junkie source through `Display`.
+/// Infix and prefix expressions are fully parenthesised, so the output
+/// parses back into the same tree
+///
+/// Nodes own their tokens, so a program can outlive the source it was parsed
+/// from, e.g. a REPL function called lines after it was defined
+pub trait Node: fmt::Display {
+    fn token_literal(&self) -> &str;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Statement {
     Let(LetStatement),
     Return(ReturnStatement),
     Expression(ExpressionStatement),
 }
 
-#[derive(Debug)]
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Let(stmt) => stmt.span,
+            Statement::Return(stmt) => stmt.span,
+            Statement::Expression(stmt) => stmt.span,
+        }
+    }
+}
+
+impl Node for Statement {
+    fn token_literal(&self) -> &str {
+        match self {
+            Statement::Let(stmt) => stmt.token_literal(),
+            Statement::Return(stmt) => stmt.token_literal(),
+            Statement::Expression(stmt) => stmt.token_literal(),
+        }
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Statement::Let(stmt) => write!(f, "{}", stmt),
+            Statement::Return(stmt) => write!(f, "{}", stmt),
+            Statement::Expression(stmt) => write!(f, "{}", stmt),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum Expression {
     Identifier(Identifier),
-    Literal(Literal),
+    Integer(IntegerLiteral),
+    Float(FloatLiteral),
+    Boolean(BooleanLiteral),
+    String(StringLiteral),
+    Prefix(PrefixExpression),
+    Infix(InfixExpression),
+    Assign(AssignExpression),
+    If(IfExpression),
+    Function(FunctionLiteral),
+    Call(CallExpression),
+    Array(ArrayLiteral),
+    Index(IndexExpression),
+    Hash(HashLiteral),
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Identifier(ident) => ident.span,
+            Expression::Integer(int) => int.span,
+            Expression::Float(float) => float.span,
+            Expression::Boolean(boolean) => boolean.span,
+            Expression::String(string) => string.span,
+            Expression::Prefix(prefix) => prefix.span,
+            Expression::Infix(infix) => infix.span,
+            Expression::Assign(assign) => assign.span,
+            Expression::If(if_expr) => if_expr.span,
+            Expression::Function(func) => func.span,
+            Expression::Call(call) => call.span,
+            Expression::Array(array) => array.span,
+            Expression::Index(index) => index.span,
+            Expression::Hash(hash) => hash.span,
+        }
+    }
+}
+
+impl Node for Expression {
+    fn token_literal(&self) -> &str {
+        match self {
+            Expression::Identifier(ident) => ident.token_literal(),
+            Expression::Integer(int) => int.token_literal(),
+            Expression::Float(float) => float.token_literal(),
+            Expression::Boolean(boolean) => boolean.token_literal(),
+            Expression::String(string) => string.token_literal(),
+            Expression::Prefix(prefix) => prefix.token_literal(),
+            Expression::Infix(infix) => infix.token_literal(),
+            Expression::Assign(assign) => assign.token_literal(),
+            Expression::If(if_expr) => if_expr.token_literal(),
+            Expression::Function(func) => func.token_literal(),
+            Expression::Call(call) => call.token_literal(),
+            Expression::Array(array) => array.token_literal(),
+            Expression::Index(index) => index.token_literal(),
+            Expression::Hash(hash) => hash.token_literal(),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Identifier(ident) => write!(f, "{}", ident),
+            Expression::Integer(int) => write!(f, "{}", int),
+            Expression::Float(float) => write!(f, "{}", float),
+            Expression::Boolean(boolean) => write!(f, "{}", boolean),
+            Expression::String(string) => write!(f, "{}", string),
+            Expression::Prefix(prefix) => write!(f, "{}", prefix),
+            Expression::Infix(infix) => write!(f, "{}", infix),
+            Expression::Assign(assign) => write!(f, "{}", assign),
+            Expression::If(if_expr) => write!(f, "{}", if_expr),
+            Expression::Function(func) => write!(f, "{}", func),
+            Expression::Call(call) => write!(f, "{}", call),
+            Expression::Array(array) => write!(f, "{}", array),
+            Expression::Index(index) => write!(f, "{}", index),
+            Expression::Hash(hash) => write!(f, "{}", hash),
+        }
+    }
+}
+
+/// `[<elements>]`
+#[derive(Debug, Clone)]
+pub struct ArrayLiteral {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub elements: Vec<Expression>,
+}
+
+impl Node for ArrayLiteral {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for ArrayLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(|e| e.to_string()).collect();
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+
+/// `<left>[<index>]`
+#[derive(Debug, Clone)]
+pub struct IndexExpression {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub left: Box<Expression>,
+    pub index: Box<Expression>,
+}
+
+impl Node for IndexExpression {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for IndexExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}[{}])", self.left, self.index)
+    }
+}
+
+/// `{<key>: <value>, ...}`, with the pairs in source order
+#[derive(Debug, Clone)]
+pub struct HashLiteral {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub pairs: Vec<(Expression, Expression)>,
 }
 
-#[derive(Debug)]
+impl Node for HashLiteral {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for HashLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        write!(f, "{{{}}}", pairs.join(", "))
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
 
 impl Node for Program {
-    // TODO: Implement this. deal with the string lifetimes lol
-    fn token_literal(&self) -> &String {
-        if self.statements.len() > 0 {
-            let statement = &self.statements[0];
-            match statement {
-                Statement::Let(_) => todo!(),
-                Statement::Return(_) => todo!(),
-                Statement::Expression(_) => todo!(),
-                _ => unimplemented!("[ERROR]: Unimplemented statement type"),
-            }
-        } else {
-            todo!();
+    fn token_literal(&self) -> &str {
+        match self.statements.first() {
+            Some(statement) => statement.token_literal(),
+            None => "",
         }
     }
+}
 
-    fn to_string(&self) -> &String {
-        todo!()
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for statement in self.statements.iter() {
+            writeln!(f, "{}", statement)?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LetStatement {
-    pub token: Token,
+    pub token: OwnedToken,
+    pub span: Span,
     pub name: Identifier,
     pub value: Expression,
 }
 
 impl Node for LetStatement {
-    fn token_literal(&self) -> &String {
+    fn token_literal(&self) -> &str {
         return &self.token.literal;
     }
+}
 
-    fn to_string(&self) -> &String {
-        // FIXME: Implement this
-        match &self.value {
-            Expression::Identifier(ident) => return &ident.name,
-            Expression::Literal(lit) => return &lit.value,
-        };
+impl fmt::Display for LetStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "let {} = {};", self.name, self.value)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReturnStatement {
-    pub token: Token,
+    pub token: OwnedToken,
+    pub span: Span,
     pub return_value: Expression,
 }
 
 impl Node for ReturnStatement {
-    fn token_literal(&self) -> &String {
+    fn token_literal(&self) -> &str {
         return &self.token.literal;
     }
+}
 
-    fn to_string(&self) -> &String {
-        match &self.return_value {
-            Expression::Identifier(ident) => return &ident.name,
-            Expression::Literal(lit) => return &lit.value,
-        };
+impl fmt::Display for ReturnStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "return {};", self.return_value)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExpressionStatement {
-    pub token: Token,
+    pub token: OwnedToken,
+    pub span: Span,
     pub expression: Expression,
 }
 
 impl Node for ExpressionStatement {
-    fn token_literal(&self) -> &String {
+    fn token_literal(&self) -> &str {
         return &self.token.literal;
     }
+}
+
+impl fmt::Display for ExpressionStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{};", self.expression)
+    }
+}
+
+/// A `{ ... }` delimited list of statements, used as the body of
+/// if/else branches and function literals
+#[derive(Debug, Clone)]
+pub struct BlockStatement {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub statements: Vec<Statement>,
+}
 
-    fn to_string(&self) -> &String {
-        match &self.expression {
-            Expression::Identifier(ident) => return &ident.name,
-            Expression::Literal(lit) => return &lit.value,
-        };
+impl Node for BlockStatement {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
     }
 }
 
-#[derive(Debug)]
+impl fmt::Display for BlockStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.statements.is_empty() {
+            return write!(f, "{{ }}");
+        }
+
+        write!(f, "{{")?;
+        for statement in self.statements.iter() {
+            write!(f, " {}", statement)?;
+        }
+        write!(f, " }}")
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Identifier {
+    pub token: OwnedToken,
+    pub span: Span,
     pub name: String,
 }
 
-#[derive(Debug)]
-pub struct Literal {
+impl Node for Identifier {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IntegerLiteral {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub value: i64,
+}
+
+impl Node for IntegerLiteral {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for IntegerLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FloatLiteral {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub value: f64,
+}
+
+impl Node for FloatLiteral {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for FloatLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Debug keeps the point or exponent, so the output lexes as a float again
+        write!(f, "{:?}", self.value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BooleanLiteral {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub value: bool,
+}
+
+impl Node for BooleanLiteral {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for BooleanLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+/// A `"..."` literal. `value` holds the text with escapes decoded, printing
+/// escapes it again
+#[derive(Debug, Clone)]
+pub struct StringLiteral {
+    pub token: OwnedToken,
+    pub span: Span,
     pub value: String,
 }
 
-// impl Program {
-//     pub fn token_literal(&self) -> String {
-//         if self.statements.len() > 0 {
-//             return self.statements[0].token_literal();
-//         } else {
-//             return String::from("");
-//         }
-//     }
-// }
+impl Node for StringLiteral {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for StringLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"")?;
+        for ch in self.value.chars() {
+            match ch {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\t' => write!(f, "\\t")?,
+                ch if ch.is_control() => write!(f, "\\u{{{:x}}}", ch as u32)?,
+                ch => write!(f, "{}", ch)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+/// `<operator><right>`, e.g. `!ok` or `-5`
+#[derive(Debug, Clone)]
+pub struct PrefixExpression {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub operator: String,
+    pub right: Box<Expression>,
+}
+
+impl Node for PrefixExpression {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for PrefixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}{})", self.operator, self.right)
+    }
+}
+
+/// `<left> <operator> <right>`, e.g. `5 * 5`
+#[derive(Debug, Clone)]
+pub struct InfixExpression {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub left: Box<Expression>,
+    pub operator: String,
+    pub right: Box<Expression>,
+}
+
+impl Node for InfixExpression {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for InfixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {} {})", self.left, self.operator, self.right)
+    }
+}
+
+/// `<name> <operator> <value>`, where operator is `=` or a compound one like `+=`
+#[derive(Debug, Clone)]
+pub struct AssignExpression {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub name: Identifier,
+    pub operator: String,
+    pub value: Box<Expression>,
+}
+
+impl Node for AssignExpression {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for AssignExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {} {})", self.name, self.operator, self.value)
+    }
+}
+
+/// `if (<condition>) { <consequence> } else { <alternative> }`
+#[derive(Debug, Clone)]
+pub struct IfExpression {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub condition: Box<Expression>,
+    pub consequence: BlockStatement,
+    pub alternative: Option<BlockStatement>,
+}
+
+impl Node for IfExpression {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for IfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Prefix and infix conditions already print their own parentheses
+        match *self.condition {
+            Expression::Prefix(_) | Expression::Infix(_) | Expression::Assign(_) => {
+                write!(f, "if {} {}", self.condition, self.consequence)?
+            }
+            _ => write!(f, "if ({}) {}", self.condition, self.consequence)?,
+        }
+
+        if let Some(alternative) = &self.alternative {
+            write!(f, " else {}", alternative)?;
+        }
+        Ok(())
+    }
+}
+
+/// `fn(<parameters>) { <body> }`
+#[derive(Debug, Clone)]
+pub struct FunctionLiteral {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+}
+
+impl Node for FunctionLiteral {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for FunctionLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parameters: Vec<String> = self.parameters.iter().map(|p| p.to_string()).collect();
+        write!(f, "fn({}) {}", parameters.join(", "), self.body)
+    }
+}
+
+/// `<function>(<arguments>)`, where function is an identifier or a function literal
+#[derive(Debug, Clone)]
+pub struct CallExpression {
+    pub token: OwnedToken,
+    pub span: Span,
+    pub function: Box<Expression>,
+    pub arguments: Vec<Expression>,
+}
+
+impl Node for CallExpression {
+    fn token_literal(&self) -> &str {
+        return &self.token.literal;
+    }
+}
+
+impl fmt::Display for CallExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arguments: Vec<String> = self.arguments.iter().map(|a| a.to_string()).collect();
+        write!(f, "{}({})", self.function, arguments.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_display() {
+        let tests = [
+            ("let myVar = anotherVar;", "let myVar = anotherVar;\n"),
+            ("return -a * b", "return ((-a) * b);\n"),
+            (
+                "a + b * c + d / e - f",
+                "(((a + (b * c)) + (d / e)) - f);\n",
+            ),
+            ("!(true == false)", "(!(true == false));\n"),
+            (
+                "if (x < y) { x } else { y }",
+                "if (x < y) { x; } else { y; };\n",
+            ),
+            ("if (ok) {}", "if (ok) { };\n"),
+            (
+                "let add = fn(x, y) { return x + y; };",
+                "let add = fn(x, y) { return (x + y); };\n",
+            ),
+            ("add(1, 2 * 3, fn() {})", "add(1, (2 * 3), fn() { });\n"),
+            ("let a = 1; a", "let a = 1;\na;\n"),
+            ("1.50 * 2e3 + 1e-9", "((1.5 * 2000.0) + 1e-9);\n"),
+            (
+                r#""a\tb" + "\"q\"\\\u{7}\u{e9}""#,
+                "(\"a\\tb\" + \"\\\"q\\\"\\\\\\u{7}é\");\n",
+            ),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_eq!(
+                parse(input).unwrap().to_string(),
+                *expected,
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_token_literal() {
+        let program = parse("let a = 1; return a;").unwrap();
+        assert_eq!(program.token_literal(), "let");
+        assert_eq!(program.statements[1].token_literal(), "return");
+        assert_eq!(parse("").unwrap().token_literal(), "");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let inputs = [
+            "let x = 5; let y = true; return x;",
+            "-a * b + !c",
+            "a + b * c + d / e - f",
+            "5 > 4 == 3 < 4 != !false",
+            "(5 + 5) * 2 / (1 - -3)",
+            "if (!ok) { 1 } else { if (x) { return 2; } }",
+            "if (a < b) { a }",
+            "let add = fn(x, y) { x + y; }; add(1, add(2, 3));",
+            "fn(f) { fn(x) { f(f(x)) } }(fn(n) { n * 2 })(3)",
+            "a * (b + c)(d)",
+            "let xs = [1, [2, 3], fn(x) { x }, []]; xs[1][0] * -xs[2](4)[0]",
+            "x = y += a % b <= c && !d || e >= 1; if (x = 1) { x -= 1 }",
+            "let pi = 3.141592653589793; 0.1 + 2.5e-7 * 1e300 - 1e16",
+            "let min = -9223372036854775808; min * -0x8000_0000_0000_0000",
+            r#"let s = "tab\there \"quoted\" back\\slash \u{1b}[0m"; s + "\n""#,
+            r#"let h = {"a": {1: [true]}, b + 1: fn() { {} }}; h["a"][1][0]"#,
+        ];
+
+        for input in inputs.iter() {
+            let program = parse(input).unwrap();
+            let printed = program.to_string();
+            let reparsed = parse(&printed).unwrap();
+
+            assert_eq!(
+                printer::sexp(&reparsed),
+                printer::sexp(&program),
+                "input: {}\nprinted: {}",
+                input,
+                printed
+            );
+            assert_eq!(reparsed.to_string(), printed);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Debug views of the AST: an indented tree and S-expressions
+
+use super::{BlockStatement, Expression, Program, Statement};
+
+/// Intermediate form shared by the tree printer: a label and its children
+struct TreeNode {
+    label: String,
+    children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    fn leaf(label: String) -> TreeNode {
+        return TreeNode {
+            label,
+            children: Vec::new(),
+        };
+    }
+
+    fn new(label: String, children: Vec<TreeNode>) -> TreeNode {
+        return TreeNode { label, children };
+    }
+}
+
+/// Renders the program as an indented tree, one node per line
+pub fn tree(program: &Program) -> String {
+    let root = TreeNode::new(
+        "Program".to_string(),
+        program.statements.iter().map(statement_node).collect(),
+    );
+
+    let mut out = String::new();
+    out.push_str(&root.label);
+    out.push('\n');
+    write_children(&root, "", &mut out);
+    return out;
+}
+
+fn write_children(node: &TreeNode, prefix: &str, out: &mut String) {
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i == node.children.len() - 1;
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        out.push_str(prefix);
+        out.push_str(branch);
+        out.push_str(&child.label);
+        out.push('\n');
+        write_children(child, &format!("{}{}", prefix, indent), out);
+    }
+}
+
+fn statement_node(statement: &Statement) -> TreeNode {
+    match statement {
+        Statement::Let(stmt) => TreeNode::new(
+            format!("Let {}", stmt.name.name),
+            vec![expression_node(&stmt.value)],
+        ),
+        Statement::Return(stmt) => TreeNode::new(
+            "Return".to_string(),
+            vec![expression_node(&stmt.return_value)],
+        ),
+        Statement::Expression(stmt) => expression_node(&stmt.expression),
+    }
+}
+
+fn block_node(label: &str, block: &BlockStatement) -> TreeNode {
+    return TreeNode::new(
+        label.to_string(),
+        block.statements.iter().map(statement_node).collect(),
+    );
+}
+
+fn expression_node(expression: &Expression) -> TreeNode {
+    match expression {
+        Expression::Identifier(ident) => TreeNode::leaf(format!("Identifier {}", ident.name)),
+        Expression::Integer(int) => TreeNode::leaf(format!("Integer {}", int.value)),
+        Expression::Float(float) => TreeNode::leaf(format!("Float {}", float)),
+        Expression::Boolean(boolean) => TreeNode::leaf(format!("Boolean {}", boolean.value)),
+        Expression::String(string) => TreeNode::leaf(format!("String {}", string)),
+        Expression::Prefix(prefix) => TreeNode::new(
+            format!("Prefix {}", prefix.operator),
+            vec![expression_node(&prefix.right)],
+        ),
+        Expression::Infix(infix) => TreeNode::new(
+            format!("Infix {}", infix.operator),
+            vec![expression_node(&infix.left), expression_node(&infix.right)],
+        ),
+        Expression::Assign(assign) => TreeNode::new(
+            format!("Assign {}", assign.operator),
+            vec![
+                TreeNode::leaf(format!("Identifier {}", assign.name.name)),
+                expression_node(&assign.value),
+            ],
+        ),
+        Expression::If(if_expr) => {
+            let mut children = vec![
+                TreeNode::new(
+                    "Condition".to_string(),
+                    vec![expression_node(&if_expr.condition)],
+                ),
+                block_node("Then", &if_expr.consequence),
+            ];
+            if let Some(alternative) = &if_expr.alternative {
+                children.push(block_node("Else", alternative));
+            }
+            TreeNode::new("If".to_string(), children)
+        }
+        Expression::Function(func) => {
+            let parameters: Vec<&str> = func.parameters.iter().map(|p| p.name.as_str()).collect();
+            TreeNode::new(
+                format!("Function ({})", parameters.join(", ")),
+                func.body.statements.iter().map(statement_node).collect(),
+            )
+        }
+        Expression::Call(call) => {
+            let mut children = vec![expression_node(&call.function)];
+            children.extend(call.arguments.iter().map(expression_node));
+            TreeNode::new("Call".to_string(), children)
+        }
+        Expression::Array(array) => TreeNode::new(
+            "Array".to_string(),
+            array.elements.iter().map(expression_node).collect(),
+        ),
+        Expression::Index(index) => TreeNode::new(
+            "Index".to_string(),
+            vec![expression_node(&index.left), expression_node(&index.index)],
+        ),
+        Expression::Hash(hash) => TreeNode::new(
+            "Hash".to_string(),
+            hash.pairs
+                .iter()
+                .map(|(key, value)| {
+                    TreeNode::new(
+                        "Pair".to_string(),
+                        vec![expression_node(key), expression_node(value)],
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Renders the program as S-expressions, one statement per line
+pub fn sexp(program: &Program) -> String {
+    let mut out = String::new();
+    for statement in program.statements.iter() {
+        out.push_str(&statement_sexp(statement));
+        out.push('\n');
+    }
+    return out;
+}
+
+fn statement_sexp(statement: &Statement) -> String {
+    match statement {
+        Statement::Let(stmt) => {
+            format!("(let {} {})", stmt.name.name, expression_sexp(&stmt.value))
+        }
+        Statement::Return(stmt) => format!("(return {})", expression_sexp(&stmt.return_value)),
+        Statement::Expression(stmt) => expression_sexp(&stmt.expression),
+    }
+}
+
+fn block_sexp(block: &BlockStatement) -> String {
+    let mut out = "(block".to_string();
+    for statement in block.statements.iter() {
+        out.push(' ');
+        out.push_str(&statement_sexp(statement));
+    }
+    out.push(')');
+    return out;
+}
+
+fn expression_sexp(expression: &Expression) -> String {
+    match expression {
+        Expression::Identifier(ident) => ident.name.clone(),
+        Expression::Integer(int) => int.value.to_string(),
+        Expression::Float(float) => float.to_string(),
+        Expression::Boolean(boolean) => boolean.value.to_string(),
+        Expression::String(string) => string.to_string(),
+        Expression::Prefix(prefix) => {
+            format!("({} {})", prefix.operator, expression_sexp(&prefix.right))
+        }
+        Expression::Infix(infix) => format!(
+            "({} {} {})",
+            infix.operator,
+            expression_sexp(&infix.left),
+            expression_sexp(&infix.right)
+        ),
+        Expression::Assign(assign) => format!(
+            "({} {} {})",
+            assign.operator,
+            assign.name.name,
+            expression_sexp(&assign.value)
+        ),
+        Expression::If(if_expr) => {
+            let mut out = format!(
+                "(if {} {}",
+                expression_sexp(&if_expr.condition),
+                block_sexp(&if_expr.consequence)
+            );
+            if let Some(alternative) = &if_expr.alternative {
+                out.push(' ');
+                out.push_str(&block_sexp(alternative));
+            }
+            out.push(')');
+            out
+        }
+        Expression::Function(func) => {
+            let parameters: Vec<&str> = func.parameters.iter().map(|p| p.name.as_str()).collect();
+            format!("(fn ({}) {})", parameters.join(" "), block_sexp(&func.body))
+        }
+        Expression::Call(call) => {
+            let mut out = format!("(call {}", expression_sexp(&call.function));
+            for argument in call.arguments.iter() {
+                out.push(' ');
+                out.push_str(&expression_sexp(argument));
+            }
+            out.push(')');
+            out
+        }
+        Expression::Array(array) => {
+            let mut out = "(array".to_string();
+            for element in array.elements.iter() {
+                out.push(' ');
+                out.push_str(&expression_sexp(element));
+            }
+            out.push(')');
+            out
+        }
+        Expression::Index(index) => format!(
+            "(index {} {})",
+            expression_sexp(&index.left),
+            expression_sexp(&index.index)
+        ),
+        Expression::Hash(hash) => {
+            let mut out = "(hash".to_string();
+            for (key, value) in hash.pairs.iter() {
+                out.push_str(&format!(
+                    " ({} {})",
+                    expression_sexp(key),
+                    expression_sexp(value)
+                ));
+            }
+            out.push(')');
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_sexp() {
+        let program = parse(
+            "let add = fn(x, y) { return x + y * 2; };
+            if (!ok) { add(1, -2) } else { false }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            sexp(&program),
+            "(let add (fn (x y) (block (return (+ x (* y 2))))))
+(if (! ok) (block (call add 1 (- 2))) (block false))
+"
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        let program = parse("let a = 1 + f(2); if (a) { return a; }").unwrap();
+
+        assert_eq!(
+            tree(&program),
+            "Program
+├── Let a
+│   └── Infix +
+│       ├── Integer 1
+│       └── Call
+│           ├── Identifier f
+│           └── Integer 2
+└── If
+    ├── Condition
+    │   └── Identifier a
+    └── Then
+        └── Return
+            └── Identifier a
+"
+        );
+    }
+}
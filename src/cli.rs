@@ -0,0 +1,245 @@
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::process::ExitCode;
+
+use junkie::diagnostics::{Diagnostic, Renderer};
+use junkie::{Error, Interpreter, Object};
+
+use crate::repl;
+
+// Exit codes, following BSD sysexits.h
+const EXIT_USAGE: u8 = 64;
+const EXIT_PARSE_ERROR: u8 = 65;
+const EXIT_NO_INPUT: u8 = 66;
+const EXIT_RUNTIME_ERROR: u8 = 70;
+
+const USAGE: &str = "Usage:
+    junkie [repl]            start an interactive session
+    junkie run <file>        run a script, `-` reads it from stdin
+    junkie -e <source>       evaluate source given on the command line
+
+Options:
+    --no-color               never colour diagnostics
+    -h, --help               print this message
+
+Exit codes:
+    0    success
+    64   invalid command line
+    65   the script failed to parse
+    66   the script could not be read
+    70   the script raised a runtime error";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Repl,
+    Run(String),
+    Eval(String),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    command: Command,
+    color: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut color = true;
+    let mut positional: Vec<&str> = Vec::new();
+    let mut source = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-color" => color = false,
+            "-h" | "--help" => {
+                return Ok(Options {
+                    command: Command::Help,
+                    color,
+                })
+            }
+            "-e" => match args.next() {
+                Some(arg) => source = Some(arg.clone()),
+                None => return Err("-e expects the source to evaluate".to_string()),
+            },
+            "-" => positional.push(arg),
+            arg if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            arg => positional.push(arg),
+        }
+    }
+
+    let command = match (source, positional.as_slice()) {
+        (Some(source), []) => Command::Eval(source),
+        (Some(_), _) => return Err("-e cannot be combined with a command".to_string()),
+        (None, []) | (None, ["repl"]) => Command::Repl,
+        (None, ["run", path]) => Command::Run(path.to_string()),
+        (None, ["run"]) => return Err("run expects a file, or `-` for stdin".to_string()),
+        (None, ["run", _, extra, ..]) | (None, ["repl", extra, ..]) => {
+            return Err(format!("unexpected argument `{}`", extra))
+        }
+        (None, [command, ..]) => return Err(format!("unknown command `{}`", command)),
+    };
+
+    return Ok(Options { command, color });
+}
+
+/// Entry point of the binary, `args` excludes the program name
+pub fn run(args: &[String]) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let color = options.color && env::var_os("NO_COLOR").is_none();
+
+    match options.command {
+        Command::Help => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Command::Repl => {
+            repl::start(color && io::stdout().is_terminal());
+            return ExitCode::SUCCESS;
+        }
+        Command::Eval(source) => {
+            let renderer = Renderer::new(color && io::stderr().is_terminal());
+            return execute(&source, "<command line>", renderer);
+        }
+        Command::Run(path) => {
+            let renderer = Renderer::new(color && io::stderr().is_terminal());
+            let (source, origin) = match read_script(&path) {
+                Ok(script) => script,
+                Err(error) => {
+                    eprintln!("error: could not read `{}`: {}", path, error);
+                    return ExitCode::from(EXIT_NO_INPUT);
+                }
+            };
+            return execute(&source, &origin, renderer);
+        }
+    }
+}
+
+/// Reads the script at `path`, or stdin for `-`. Returns it with the name to show in diagnostics
+fn read_script(path: &str) -> io::Result<(String, String)> {
+    if path == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        return Ok((source, "<stdin>".to_string()));
+    }
+    return Ok((fs::read_to_string(path)?, path.to_string()));
+}
+
+/// Parses and evaluates `source`, printing the final value unless it is null
+fn execute(source: &str, origin: &str, renderer: Renderer) -> ExitCode {
+    let mut interpreter = Interpreter::new();
+
+    match interpreter.eval_str(source) {
+        Ok(Object::Null) => {}
+        Ok(result) => println!("{}", result),
+        Err(Error::Parse(errors)) => {
+            for error in errors.iter() {
+                eprint!(
+                    "{}",
+                    renderer.render(&Diagnostic::from(error), source, origin)
+                );
+            }
+            return ExitCode::from(EXIT_PARSE_ERROR);
+        }
+        Err(Error::Runtime(error)) => {
+            eprint!(
+                "{}",
+                renderer.render(&Diagnostic::from(&error), source, origin)
+            );
+            return ExitCode::from(EXIT_RUNTIME_ERROR);
+        }
+    }
+
+    return ExitCode::SUCCESS;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        return parse_args(&args);
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let tests = [
+            (vec![], Command::Repl),
+            (vec!["repl"], Command::Repl),
+            (
+                vec!["run", "main.junk"],
+                Command::Run("main.junk".to_string()),
+            ),
+            (vec!["run", "-"], Command::Run("-".to_string())),
+            (vec!["-e", "1 + 2"], Command::Eval("1 + 2".to_string())),
+            (vec!["--help"], Command::Help),
+            (vec!["run", "x.junk", "-h"], Command::Help),
+        ];
+
+        for (args, expected) in tests {
+            let options = parse(&args).unwrap();
+            assert_eq!(options.command, expected, "args: {:?}", args);
+            assert!(options.color);
+        }
+    }
+
+    #[test]
+    fn test_parse_no_color() {
+        let options = parse(&["--no-color", "run", "main.junk"]).unwrap();
+        assert_eq!(
+            options,
+            Options {
+                command: Command::Run("main.junk".to_string()),
+                color: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_arguments() {
+        let tests = [
+            (vec!["run"], "run expects a file, or `-` for stdin"),
+            (vec!["-e"], "-e expects the source to evaluate"),
+            (
+                vec!["-e", "1", "run", "x"],
+                "-e cannot be combined with a command",
+            ),
+            (vec!["--verbose"], "unknown option `--verbose`"),
+            (vec!["build"], "unknown command `build`"),
+            (vec!["run", "a", "b"], "unexpected argument `b`"),
+            (vec!["repl", "a"], "unexpected argument `a`"),
+        ];
+
+        for (args, expected) in tests {
+            assert_eq!(parse(&args), Err(expected.to_string()), "args: {:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_execute_exit_codes() {
+        let renderer = Renderer::new(false);
+
+        assert_eq!(
+            execute("let a = 1; a + 1", "<test>", renderer),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(
+            execute("let a 1;", "<test>", renderer),
+            ExitCode::from(EXIT_PARSE_ERROR)
+        );
+        assert_eq!(
+            execute("1 + true", "<test>", renderer),
+            ExitCode::from(EXIT_RUNTIME_ERROR)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt::Write;
+
+use crate::lexer::LexError;
+use crate::object::RuntimeError;
+use crate::parser::ParseError;
+use crate::token::Span;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// An error message tied to a location in the source, ready to be rendered
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    /// Short note printed next to the caret underline
+    pub label: Option<String>,
+    /// Suggestion printed below the snippet
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: String, span: Span) -> Diagnostic {
+        return Diagnostic {
+            message,
+            span,
+            label: None,
+            help: None,
+        };
+    }
+
+    pub fn with_label(mut self, label: &str) -> Diagnostic {
+        self.label = Some(label.to_string());
+        return self;
+    }
+
+    pub fn with_help(mut self, help: &str) -> Diagnostic {
+        self.help = Some(help.to_string());
+        return self;
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Diagnostic {
+        let diagnostic = Diagnostic::new(error.to_string(), error.span());
+
+        match error {
+            ParseError::UnexpectedToken { expected, .. } => {
+                diagnostic.with_label(&format!("expected {} here", expected))
+            }
+            ParseError::UnterminatedBlock { .. } => diagnostic
+                .with_label("this block is never closed")
+                .with_help("add a `}` to close the block"),
+            ParseError::IllegalToken { .. } => diagnostic.with_label("not valid in Junkie source"),
+            ParseError::MissingPrefixParseFn { .. } => {
+                diagnostic.with_label("expected an expression")
+            }
+            ParseError::InvalidAssignmentTarget { .. } => {
+                diagnostic.with_label("cannot assign to this expression")
+            }
+            ParseError::NestedTooDeeply { .. } => diagnostic
+                .with_label("nested too deeply")
+                .with_help("move inner parts into `let` bindings or functions"),
+            ParseError::Lexical { error } => match error {
+                LexError::UnterminatedString { .. } => diagnostic
+                    .with_label("this string is never closed")
+                    .with_help("add a `\"` to close the string"),
+                LexError::UnterminatedComment { .. } => diagnostic
+                    .with_label("this comment is never closed")
+                    .with_help("block comments nest, every `/*` needs its own `*/`"),
+                LexError::IntegerOutOfRange { .. } => diagnostic
+                    .with_label("integer literal is out of range")
+                    .with_help("integers must fit in a signed 64-bit value"),
+                LexError::InvalidInteger { .. } => diagnostic
+                    .with_label("not a valid integer")
+                    .with_help("prefix hexadecimal with 0x, octal with 0o and binary with 0b"),
+                LexError::FloatOutOfRange { .. } => diagnostic
+                    .with_label("float literal is out of range")
+                    .with_help("floats must fit in a 64-bit IEEE 754 value"),
+                LexError::MissingIntegerPart { literal, .. } => diagnostic
+                    .with_label("write a `0` before the point")
+                    .with_help(&format!("use `0{}`", literal)),
+                LexError::InvalidEscape { .. } => diagnostic
+                    .with_label("unknown escape")
+                    .with_help("valid escapes are \\n, \\t, \\\", \\\\ and \\u{...}"),
+                LexError::InvalidUnicodeEscape { .. } => diagnostic
+                    .with_label("not a unicode scalar value")
+                    .with_help("write one to six hex digits, e.g. \\u{1F600}"),
+            },
+        }
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Diagnostic {
+        return Diagnostic::new(error.message.clone(), error.span);
+    }
+}
+
+/// Renders diagnostics rustc style: the message, the offending source line
+/// and a `^^^` underline below the span
+#[derive(Debug, Clone, Copy)]
+pub struct Renderer {
+    color: bool,
+}
+
+impl Renderer {
+    pub fn new(color: bool) -> Renderer {
+        return Renderer { color };
+    }
+
+    /// Renders `diagnostic` against `source`, which is called `origin` in the output
+    pub fn render(&self, diagnostic: &Diagnostic, source: &str, origin: &str) -> String {
+        let span = diagnostic.span;
+        let mut out = String::new();
+
+        // Clamp and snap to char boundaries, so a stale span never slices out of bounds
+        let start = floor_char_boundary(source, span.start);
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line = &source[line_start..line_end];
+        let end = ceil_char_boundary(source, span.end).clamp(start, line_end);
+
+        // Keep tabs so the carets line up with the source line above them
+        let padding: String = source[line_start..start]
+            .chars()
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(source[start..end].chars().count().max(1));
+
+        let line_number = span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        let _ = writeln!(
+            out,
+            "{}: {}",
+            self.paint(RED, "error"),
+            self.paint(BOLD, &diagnostic.message)
+        );
+        let _ = writeln!(
+            out,
+            "{}{} {}:{}:{}",
+            gutter,
+            self.paint(BLUE, "-->"),
+            origin,
+            span.line,
+            span.column
+        );
+        let _ = writeln!(out, "{} {}", gutter, self.paint(BLUE, "|"));
+        let _ = writeln!(
+            out,
+            "{} {} {}",
+            self.paint(BLUE, &line_number),
+            self.paint(BLUE, "|"),
+            line
+        );
+
+        let underline = match &diagnostic.label {
+            Some(label) => format!("{} {}", carets, label),
+            None => carets,
+        };
+        let _ = writeln!(
+            out,
+            "{} {} {}{}",
+            gutter,
+            self.paint(BLUE, "|"),
+            padding,
+            self.paint(RED, &underline)
+        );
+
+        if let Some(help) = &diagnostic.help {
+            let _ = writeln!(out, "{} {}", gutter, self.paint(BLUE, "|"));
+            let _ = writeln!(
+                out,
+                "{} {} {}: {}",
+                gutter,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "help"),
+                help
+            );
+        }
+
+        return out;
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color {
+            return format!("{}{}{}", color, text, RESET);
+        }
+        return text.to_string();
+    }
+}
+
+/// Largest char boundary of `source` that is not after `index`
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    return index;
+}
+
+/// Smallest char boundary of `source` that is not before `index`
+fn ceil_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index += 1;
+    }
+    return index;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_render_snaps_to_char_boundaries() {
+        let input = "\"éé\" + 1";
+        let span = Span {
+            start: 2,
+            end: 4,
+            line: 1,
+            column: 2,
+        };
+
+        let diagnostic = Diagnostic::new("bad".to_string(), span);
+        let rendered = Renderer::new(false).render(&diagnostic, input, "test.junk");
+        assert_eq!(
+            rendered,
+            "error: bad
+ --> test.junk:1:2
+  |
+1 | \"éé\" + 1
+  |  ^^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_parse_error() {
+        let input = "let a = 1;\nlet x 5;";
+        let errors = parse(input).unwrap_err();
+
+        let rendered =
+            Renderer::new(false).render(&Diagnostic::from(&errors[0]), input, "test.junk");
+        assert_eq!(
+            rendered,
+            "error: expected `=`, found integer
+ --> test.junk:2:7
+  |
+2 | let x 5;
+  |       ^ expected `=` here
+"
+        );
+    }
+
+    #[test]
+    fn test_render_help_and_multichar_span() {
+        let input = "\tlet big = 9999999999999999999;";
+        let errors = parse(input).unwrap_err();
+
+        let rendered = Renderer::new(false).render(&Diagnostic::from(&errors[0]), input, "<repl>");
+        assert_eq!(
+            rendered,
+            "error: integer literal `9999999999999999999` is out of range
+ --> <repl>:1:12
+  |
+1 | \tlet big = 9999999999999999999;
+  | \t          ^^^^^^^^^^^^^^^^^^^ integer literal is out of range
+  |
+  = help: integers must fit in a signed 64-bit value
+"
+        );
+    }
+
+    #[test]
+    fn test_render_end_of_input() {
+        let input = "fn(x) { x";
+
+        let diagnostic = Diagnostic::new(
+            "unexpected end of input".to_string(),
+            Span {
+                start: input.len(),
+                end: input.len(),
+                line: 1,
+                column: 10,
+            },
+        );
+        let rendered = Renderer::new(false).render(&diagnostic, input, "<repl>");
+        assert_eq!(
+            rendered,
+            "error: unexpected end of input
+ --> <repl>:1:10
+  |
+1 | fn(x) { x
+  |          ^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_runtime_error_with_color() {
+        let input = "1 + true";
+        let error = RuntimeError {
+            message: "type mismatch: INTEGER + BOOLEAN".to_string(),
+            span: Span {
+                start: 0,
+                end: 8,
+                line: 1,
+                column: 1,
+            },
+        };
+
+        let rendered = Renderer::new(true).render(&Diagnostic::from(&error), input, "<repl>");
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m: "));
+        assert!(rendered.contains("\x1b[1;31m^^^^^^^^\x1b[0m"));
+
+        let plain = Renderer::new(false).render(&Diagnostic::from(&error), input, "<repl>");
+        assert!(!plain.contains('\x1b'));
+    }
+}
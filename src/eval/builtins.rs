@@ -0,0 +1,129 @@
+use std::rc::Rc;
+
+use crate::object::{Builtin, Object};
+
+type NativeFn = fn(&[Object]) -> Result<Object, String>;
+
+/// Native functions, looked up by name when an identifier is not bound in the environment
+const NATIVES: &[(&str, NativeFn)] = &[
+    ("len", len),
+    ("first", first),
+    ("last", last),
+    ("rest", rest),
+    ("push", push),
+    ("puts", puts),
+];
+
+thread_local! {
+    /// Built once per thread, so that a lookup only clones the `Rc`s of an existing builtin
+    static BUILTINS: Vec<Builtin> = NATIVES
+        .iter()
+        .map(|&(name, function)| Builtin::new(name, function))
+        .collect();
+}
+
+pub fn lookup(name: &str) -> Option<Object> {
+    return BUILTINS.with(|builtins| {
+        builtins
+            .iter()
+            .find(|builtin| &*builtin.name == name)
+            .map(|builtin| Object::Builtin(builtin.clone()))
+    });
+}
+
+fn check_arity(arguments: &[Object], want: usize) -> Result<(), String> {
+    if arguments.len() != want {
+        return Err(format!(
+            "wrong number of arguments: want={}, got={}",
+            want,
+            arguments.len()
+        ));
+    }
+    return Ok(());
+}
+
+fn unsupported(name: &str, argument: &Object) -> String {
+    return format!(
+        "argument to `{}` not supported, got {}",
+        name,
+        argument.type_name()
+    );
+}
+
+/// Length of an array or hash, or the number of characters in a string
+fn len(arguments: &[Object]) -> Result<Object, String> {
+    check_arity(arguments, 1)?;
+    let length = match &arguments[0] {
+        Object::String(value) => value.chars().count(),
+        Object::Array(elements) => elements.len(),
+        Object::Hash(pairs) => pairs.len(),
+        other => return Err(unsupported("len", other)),
+    };
+    return Ok(Object::Integer(length as i64));
+}
+
+/// First element of an array, or null if it is empty
+fn first(arguments: &[Object]) -> Result<Object, String> {
+    check_arity(arguments, 1)?;
+    match &arguments[0] {
+        Object::Array(elements) => Ok(elements.first().cloned().unwrap_or(Object::Null)),
+        other => Err(unsupported("first", other)),
+    }
+}
+
+/// Last element of an array, or null if it is empty
+fn last(arguments: &[Object]) -> Result<Object, String> {
+    check_arity(arguments, 1)?;
+    match &arguments[0] {
+        Object::Array(elements) => Ok(elements.last().cloned().unwrap_or(Object::Null)),
+        other => Err(unsupported("last", other)),
+    }
+}
+
+/// A new array holding every element but the first, or null if it is empty
+fn rest(arguments: &[Object]) -> Result<Object, String> {
+    check_arity(arguments, 1)?;
+    match &arguments[0] {
+        Object::Array(elements) if elements.is_empty() => Ok(Object::Null),
+        Object::Array(elements) => Ok(Object::Array(Rc::new(elements[1..].to_vec()))),
+        other => Err(unsupported("rest", other)),
+    }
+}
+
+/// A new array with the value appended, the original is left unchanged
+fn push(arguments: &[Object]) -> Result<Object, String> {
+    check_arity(arguments, 2)?;
+    match &arguments[0] {
+        Object::Array(elements) => {
+            let mut elements = elements.to_vec();
+            elements.push(arguments[1].clone());
+            Ok(Object::Array(Rc::new(elements)))
+        }
+        other => Err(unsupported("push", other)),
+    }
+}
+
+/// Prints each argument on its own line
+fn puts(arguments: &[Object]) -> Result<Object, String> {
+    for argument in arguments.iter() {
+        println!("{}", argument);
+    }
+    return Ok(Object::Null);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_shares_builtins() {
+        let (first, second) = match (lookup("len"), lookup("len")) {
+            (Some(Object::Builtin(first)), Some(Object::Builtin(second))) => (first, second),
+            other => panic!("Expected two Builtins, got {:?}", other),
+        };
+
+        assert!(Rc::ptr_eq(&first.name, &second.name));
+        assert!(Rc::ptr_eq(&first.function, &second.function));
+        assert!(lookup("lens").is_none());
+    }
+}
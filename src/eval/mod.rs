@@ -0,0 +1,1241 @@
+mod builtins;
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::ast;
+use crate::object::{CapturedEnv, Env, Environment, Function, Object, RuntimeError};
+use crate::token::Span;
+
+/// How deeply expressions may be evaluated inside one another. Scripts have no
+/// loops, so recursion has to go deep; this only stops a runaway one. A call
+/// nests about three levels, for the call itself, the `if` and the operator
+const MAX_DEPTH: usize = 10_000;
+
+/// Stack left before nested evaluation moves onto a fresh segment, and that
+/// segment's size. Builtins and host functions run within the red zone
+const RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT: usize = 2 * 1024 * 1024;
+
+thread_local! {
+    /// Expressions currently being evaluated on this thread
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts an expression as being evaluated for as long as it is alive
+struct DepthGuard;
+
+impl DepthGuard {
+    /// None when evaluation would nest deeper than `MAX_DEPTH`
+    fn enter() -> Option<DepthGuard> {
+        return DEPTH.with(|depth| {
+            if depth.get() >= MAX_DEPTH {
+                return None;
+            }
+            depth.set(depth.get() + 1);
+            return Some(DepthGuard);
+        });
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Evaluates every statement of the program and returns the value of the last one.
+/// A top level `return` stops evaluation and yields the returned value unwrapped
+pub fn eval_program(program: &ast::Program, env: &Env) -> Object {
+    let mut result = Object::Null;
+
+    for statement in program.statements.iter() {
+        result = eval_statement(statement, env);
+
+        match result {
+            Object::ReturnValue(value) => return *value,
+            Object::Error(_) => return result,
+            _ => {}
+        }
+    }
+
+    return result;
+}
+
+/// Unlike `eval_program`, return values are passed up still wrapped,
+/// so that a `return` inside nested blocks also stops the enclosing ones
+fn eval_block_statement(block: &ast::BlockStatement, env: &Env) -> Object {
+    let mut result = Object::Null;
+
+    for statement in block.statements.iter() {
+        result = eval_statement(statement, env);
+
+        if matches!(result, Object::ReturnValue(_) | Object::Error(_)) {
+            return result;
+        }
+    }
+
+    return result;
+}
+
+fn eval_statement(statement: &ast::Statement, env: &Env) -> Object {
+    match statement {
+        ast::Statement::Expression(stmt) => eval_expression(&stmt.expression, env),
+        ast::Statement::Return(stmt) => {
+            let value = eval_expression(&stmt.return_value, env);
+            if value.is_abrupt() {
+                return value;
+            }
+            return Object::ReturnValue(Box::new(value));
+        }
+        ast::Statement::Let(stmt) => {
+            let value = eval_expression(&stmt.value, env);
+            if value.is_abrupt() {
+                return value;
+            }
+            env.borrow_mut().set(stmt.name.name.clone(), value);
+            return Object::Null;
+        }
+    }
+}
+
+/// Every recursion in the evaluator passes through here, so this is where depth
+/// is limited and where the stack grows onto the heap, whatever thread runs it
+fn eval_expression(expression: &ast::Expression, env: &Env) -> Object {
+    let _guard = match DepthGuard::enter() {
+        Some(guard) => guard,
+        None => {
+            return error(
+                expression.span(),
+                "maximum evaluation depth exceeded".to_string(),
+            )
+        }
+    };
+
+    return stacker::maybe_grow(RED_ZONE, STACK_SEGMENT, || {
+        eval_nested_expression(expression, env)
+    });
+}
+
+fn eval_nested_expression(expression: &ast::Expression, env: &Env) -> Object {
+    match expression {
+        ast::Expression::Integer(int) => Object::Integer(int.value),
+        ast::Expression::Float(float) => Object::Float(float.value),
+        ast::Expression::Boolean(boolean) => Object::Boolean(boolean.value),
+        ast::Expression::String(string) => Object::String(string.value.clone()),
+        ast::Expression::Prefix(prefix) => {
+            let right = eval_expression(&prefix.right, env);
+            if right.is_abrupt() {
+                return right;
+            }
+            return eval_prefix_expression(&prefix.operator, right, prefix.span);
+        }
+        ast::Expression::Infix(infix) if matches!(infix.operator.as_str(), "&&" | "||") => {
+            eval_logical_expression(infix, env)
+        }
+        ast::Expression::Infix(infix) => {
+            let left = eval_expression(&infix.left, env);
+            if left.is_abrupt() {
+                return left;
+            }
+            let right = eval_expression(&infix.right, env);
+            if right.is_abrupt() {
+                return right;
+            }
+            return eval_infix_expression(&infix.operator, left, right, infix.span);
+        }
+        ast::Expression::Assign(assign) => eval_assign_expression(assign, env),
+        ast::Expression::If(if_expr) => eval_if_expression(if_expr, env),
+        ast::Expression::Identifier(ident) => eval_identifier(ident, env),
+        ast::Expression::Function(func) => Object::Function(Function {
+            parameters: Rc::new(func.parameters.clone()),
+            body: Rc::new(func.body.clone()),
+            env: CapturedEnv::Strong(Rc::clone(env)),
+        }),
+        ast::Expression::Call(call) => {
+            let function = eval_expression(&call.function, env);
+            if function.is_abrupt() {
+                return function;
+            }
+
+            let mut arguments = Vec::with_capacity(call.arguments.len());
+            for argument in call.arguments.iter() {
+                let argument = eval_expression(argument, env);
+                if argument.is_abrupt() {
+                    return argument;
+                }
+                arguments.push(argument);
+            }
+
+            return apply_function(function, arguments, call);
+        }
+        ast::Expression::Array(array) => {
+            let mut elements = Vec::with_capacity(array.elements.len());
+            for element in array.elements.iter() {
+                let element = eval_expression(element, env);
+                if element.is_abrupt() {
+                    return element;
+                }
+                elements.push(element);
+            }
+            return Object::Array(Rc::new(elements));
+        }
+        ast::Expression::Hash(hash) => eval_hash_literal(hash, env),
+        ast::Expression::Index(index) => {
+            let left = eval_expression(&index.left, env);
+            if left.is_abrupt() {
+                return left;
+            }
+            let position = eval_expression(&index.index, env);
+            if position.is_abrupt() {
+                return position;
+            }
+            return eval_index_expression(left, position, index);
+        }
+    }
+}
+
+/// `&&` and `||` only evaluate their right side when the left one does not
+/// decide the result. Either way the result is a boolean
+fn eval_logical_expression(infix: &ast::InfixExpression, env: &Env) -> Object {
+    let left = eval_expression(&infix.left, env);
+    if left.is_abrupt() {
+        return left;
+    }
+
+    match (infix.operator.as_str(), left.is_truthy()) {
+        ("&&", false) => return Object::Boolean(false),
+        ("||", true) => return Object::Boolean(true),
+        _ => {}
+    }
+
+    let right = eval_expression(&infix.right, env);
+    if right.is_abrupt() {
+        return right;
+    }
+    return Object::Boolean(right.is_truthy());
+}
+
+/// Rebinds an existing name in whichever scope defined it and returns the new value.
+/// Compound forms like `x += 1` apply their operator to the current value first
+fn eval_assign_expression(assign: &ast::AssignExpression, env: &Env) -> Object {
+    let name = &assign.name.name;
+
+    let value = eval_expression(&assign.value, env);
+    if value.is_abrupt() {
+        return value;
+    }
+
+    let value = match assign.operator.strip_suffix('=') {
+        Some(operator) if !operator.is_empty() => {
+            let current = match env.borrow().get(name) {
+                Some(current) => current,
+                None => return error(assign.name.span, format!("identifier not found: {}", name)),
+            };
+            let value = eval_infix_expression(operator, current, value, assign.span);
+            if value.is_abrupt() {
+                return value;
+            }
+            value
+        }
+        _ => value,
+    };
+
+    if !env.borrow_mut().assign(name, value.clone()) {
+        return error(assign.name.span, format!("identifier not found: {}", name));
+    }
+    return value;
+}
+
+/// Builds an error object pointing at `span`
+fn error(span: Span, message: String) -> Object {
+    return Object::Error(RuntimeError { message, span });
+}
+
+/// Bindings in the environment shadow builtins of the same name
+fn eval_identifier(ident: &ast::Identifier, env: &Env) -> Object {
+    if let Some(value) = env.borrow().get(&ident.name) {
+        return value;
+    }
+    match builtins::lookup(&ident.name) {
+        Some(builtin) => builtin,
+        None => error(ident.span, format!("identifier not found: {}", ident.name)),
+    }
+}
+
+/// Calls `function` in a fresh scope enclosed by the environment it was defined in
+fn apply_function(function: Object, arguments: Vec<Object>, call: &ast::CallExpression) -> Object {
+    let function = match function {
+        Object::Function(function) => function,
+        Object::Builtin(builtin) => {
+            return match (builtin.function)(&arguments) {
+                Ok(result) => result,
+                Err(message) => error(call.span, message),
+            }
+        }
+        other => {
+            return error(
+                call.function.span(),
+                format!("not a function: {}", other.type_name()),
+            )
+        }
+    };
+
+    if function.parameters.len() != arguments.len() {
+        return error(
+            call.span,
+            format!(
+                "wrong number of arguments: want={}, got={}",
+                function.parameters.len(),
+                arguments.len()
+            ),
+        );
+    }
+
+    let outer = match function.env.upgrade() {
+        Some(outer) => outer,
+        None => {
+            return error(
+                call.span,
+                "function environment no longer exists".to_string(),
+            )
+        }
+    };
+
+    let enclosed = Environment::new_enclosed(&outer);
+    for (parameter, argument) in function.parameters.iter().zip(arguments) {
+        enclosed.borrow_mut().set(parameter.name.clone(), argument);
+    }
+
+    match eval_block_statement(&function.body, &enclosed) {
+        Object::ReturnValue(value) => return *value,
+        result => return result,
+    }
+}
+
+/// Evaluates the pairs in source order. A later duplicate key overwrites an earlier one
+fn eval_hash_literal(hash: &ast::HashLiteral, env: &Env) -> Object {
+    let mut pairs = BTreeMap::new();
+
+    for (key_expression, value_expression) in hash.pairs.iter() {
+        let key = eval_expression(key_expression, env);
+        if key.is_abrupt() {
+            return key;
+        }
+        let hash_key = match key.hash_key() {
+            Some(hash_key) => hash_key,
+            None => {
+                return error(
+                    key_expression.span(),
+                    format!("unusable as hash key: {}", key.type_name()),
+                )
+            }
+        };
+
+        let value = eval_expression(value_expression, env);
+        if value.is_abrupt() {
+            return value;
+        }
+        pairs.insert(hash_key, value);
+    }
+
+    return Object::Hash(Rc::new(pairs));
+}
+
+/// Indexes arrays by position and hashes by key. A key missing from a hash gives null
+fn eval_index_expression(left: Object, index: Object, expression: &ast::IndexExpression) -> Object {
+    match (&left, &index) {
+        (Object::Array(elements), Object::Integer(i)) => {
+            match usize::try_from(*i).ok().and_then(|i| elements.get(i)) {
+                Some(element) => element.clone(),
+                None => error(
+                    expression.index.span(),
+                    format!(
+                        "index out of bounds: the length is {} but the index is {}",
+                        elements.len(),
+                        i
+                    ),
+                ),
+            }
+        }
+        (Object::Hash(pairs), _) => match index.hash_key() {
+            Some(key) => pairs.get(&key).cloned().unwrap_or(Object::Null),
+            None => error(
+                expression.index.span(),
+                format!("unusable as hash key: {}", index.type_name()),
+            ),
+        },
+        _ => error(
+            expression.span,
+            format!(
+                "index operator not supported: {}[{}]",
+                left.type_name(),
+                index.type_name()
+            ),
+        ),
+    }
+}
+
+fn eval_prefix_expression(operator: &str, right: Object, span: Span) -> Object {
+    match (operator, right) {
+        ("!", right) => Object::Boolean(!right.is_truthy()),
+        ("-", Object::Integer(value)) => match value.checked_neg() {
+            Some(value) => Object::Integer(value),
+            None => error(span, format!("integer overflow: -{}", value)),
+        },
+        ("-", Object::Float(value)) => Object::Float(-value),
+        (operator, right) => error(
+            span,
+            format!("unknown operator: {}{}", operator, right.type_name()),
+        ),
+    }
+}
+
+fn eval_infix_expression(operator: &str, left: Object, right: Object, span: Span) -> Object {
+    match (&left, &right) {
+        (Object::Integer(l), Object::Integer(r)) => {
+            eval_integer_infix_expression(operator, *l, *r, span)
+        }
+        (Object::Float(l), Object::Float(r)) => eval_float_infix_expression(operator, *l, *r, span),
+        // Mixed arithmetic promotes the integer to a float
+        (Object::Integer(l), Object::Float(r)) => {
+            eval_float_infix_expression(operator, *l as f64, *r, span)
+        }
+        (Object::Float(l), Object::Integer(r)) => {
+            eval_float_infix_expression(operator, *l, *r as f64, span)
+        }
+        (Object::String(l), Object::String(r)) => match operator {
+            "+" => Object::String(format!("{}{}", l, r)),
+            "==" => Object::Boolean(l == r),
+            "!=" => Object::Boolean(l != r),
+            _ => error(
+                span,
+                format!("unknown operator: STRING {} STRING", operator),
+            ),
+        },
+        (Object::Boolean(l), Object::Boolean(r)) => match operator {
+            "==" => Object::Boolean(l == r),
+            "!=" => Object::Boolean(l != r),
+            _ => error(
+                span,
+                format!(
+                    "unknown operator: {} {} {}",
+                    left.type_name(),
+                    operator,
+                    right.type_name()
+                ),
+            ),
+        },
+        _ if left.type_name() != right.type_name() => error(
+            span,
+            format!(
+                "type mismatch: {} {} {}",
+                left.type_name(),
+                operator,
+                right.type_name()
+            ),
+        ),
+        _ => error(
+            span,
+            format!(
+                "unknown operator: {} {} {}",
+                left.type_name(),
+                operator,
+                right.type_name()
+            ),
+        ),
+    }
+}
+
+fn eval_integer_infix_expression(operator: &str, left: i64, right: i64, span: Span) -> Object {
+    let result = match operator {
+        "+" => left.checked_add(right),
+        "-" => left.checked_sub(right),
+        "*" => left.checked_mul(right),
+        "/" => {
+            if right == 0 {
+                return error(span, "division by zero".to_string());
+            }
+            left.checked_div(right)
+        }
+        "%" => {
+            if right == 0 {
+                return error(span, "division by zero".to_string());
+            }
+            // Only `i64::MIN % -1` overflows `checked_rem`, yet its remainder is simply 0
+            Some(left.wrapping_rem(right))
+        }
+        "<" => return Object::Boolean(left < right),
+        ">" => return Object::Boolean(left > right),
+        "<=" => return Object::Boolean(left <= right),
+        ">=" => return Object::Boolean(left >= right),
+        "==" => return Object::Boolean(left == right),
+        "!=" => return Object::Boolean(left != right),
+        _ => {
+            return error(
+                span,
+                format!("unknown operator: INTEGER {} INTEGER", operator),
+            );
+        }
+    };
+
+    match result {
+        Some(value) => Object::Integer(value),
+        None => error(
+            span,
+            format!("integer overflow: {} {} {}", left, operator, right),
+        ),
+    }
+}
+
+/// Follows IEEE 754, so dividing by zero gives an infinity or NaN rather than an error
+fn eval_float_infix_expression(operator: &str, left: f64, right: f64, span: Span) -> Object {
+    match operator {
+        "+" => Object::Float(left + right),
+        "-" => Object::Float(left - right),
+        "*" => Object::Float(left * right),
+        "/" => Object::Float(left / right),
+        "%" => Object::Float(left % right),
+        "<" => Object::Boolean(left < right),
+        ">" => Object::Boolean(left > right),
+        "<=" => Object::Boolean(left <= right),
+        ">=" => Object::Boolean(left >= right),
+        "==" => Object::Boolean(left == right),
+        "!=" => Object::Boolean(left != right),
+        _ => error(span, format!("unknown operator: FLOAT {} FLOAT", operator)),
+    }
+}
+
+fn eval_if_expression(if_expr: &ast::IfExpression, env: &Env) -> Object {
+    let condition = eval_expression(&if_expr.condition, env);
+    if condition.is_abrupt() {
+        return condition;
+    }
+
+    if condition.is_truthy() {
+        return eval_block_statement(&if_expr.consequence, env);
+    } else if let Some(alternative) = &if_expr.alternative {
+        return eval_block_statement(alternative, env);
+    } else {
+        return Object::Null;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::object::HashKey;
+    use crate::parser::Parser;
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+
+    fn test_eval(input: &str) -> Object {
+        let env = Rc::new(RefCell::new(Environment::new()));
+        return test_eval_in(input, &env);
+    }
+
+    fn test_eval_in(input: &str, env: &Env) -> Object {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = match parser.parse_program() {
+            Ok(program) => program,
+            Err(errors) => panic!("Parser has {} errors: {:?}", errors.len(), errors),
+        };
+
+        return eval_program(&program, env);
+    }
+
+    fn assert_integer(object: &Object, expected: i64) {
+        match object {
+            Object::Integer(value) => assert_eq!(*value, expected),
+            _ => panic!("Expected Integer({}), got {:?}", expected, object),
+        }
+    }
+
+    fn assert_boolean(object: &Object, expected: bool) {
+        match object {
+            Object::Boolean(value) => assert_eq!(*value, expected),
+            _ => panic!("Expected Boolean({}), got {:?}", expected, object),
+        }
+    }
+
+    #[test]
+    fn test_eval_integer_expression() {
+        let tests = [
+            ("5", 5),
+            ("10", 10),
+            ("-5", -5),
+            ("-10", -10),
+            ("5 + 5 + 5 + 5 - 10", 10),
+            ("2 * 2 * 2 * 2 * 2", 32),
+            ("-50 + 100 + -50", 0),
+            ("5 * 2 + 10", 20),
+            ("5 + 2 * 10", 25),
+            ("20 + 2 * -10", 0),
+            ("50 / 2 * 2 + 10", 60),
+            ("2 * (5 + 10)", 30),
+            ("3 * 3 * 3 + 10", 37),
+            ("3 * (3 * 3) + 10", 37),
+            ("(5 + 10 * 2 + 15 / 3) * 2 + -10", 50),
+            ("17 % 5", 2),
+            ("-17 % 5", -2),
+            ("2 + 10 % 4 * 3", 8),
+            ("-9223372036854775808", i64::MIN),
+            ("-9223372036854775808 + 9223372036854775807", -1),
+            ("-9223372036854775808 % -1", 0),
+            ("7 % -1", 0),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_integer(&test_eval(input), *expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_boolean_expression() {
+        let tests = [
+            ("true", true),
+            ("false", false),
+            ("1 < 2", true),
+            ("1 > 2", false),
+            ("1 < 1", false),
+            ("1 == 1", true),
+            ("1 != 1", false),
+            ("1 == 2", false),
+            ("1 != 2", true),
+            ("true == true", true),
+            ("false == false", true),
+            ("true == false", false),
+            ("true != false", true),
+            ("(1 < 2) == true", true),
+            ("(1 > 2) == true", false),
+            ("1 <= 2", true),
+            ("2 <= 2", true),
+            ("3 <= 2", false),
+            ("2 >= 2", true),
+            ("1 >= 2", false),
+            ("2.5 >= 2", true),
+            ("true && true", true),
+            ("true && false", false),
+            ("false || true", true),
+            ("false || false", false),
+            ("1 && 0", true),
+            ("1 < 2 && 2 < 3 || false", true),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_boolean(&test_eval(input), *expected);
+        }
+    }
+
+    #[test]
+    fn test_bang_operator() {
+        let tests = [
+            ("!true", false),
+            ("!false", true),
+            ("!5", false),
+            ("!!true", true),
+            ("!!false", false),
+            ("!!5", true),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_boolean(&test_eval(input), *expected);
+        }
+    }
+
+    #[test]
+    fn test_if_else_expressions() {
+        let tests = [
+            ("if (true) { 10 }", Some(10)),
+            ("if (false) { 10 }", None),
+            ("if (1) { 10 }", Some(10)),
+            ("if (1 < 2) { 10 }", Some(10)),
+            ("if (1 > 2) { 10 }", None),
+            ("if (1 > 2) { 10 } else { 20 }", Some(20)),
+            ("if (1 < 2) { 10 } else { 20 }", Some(10)),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let evaluated = test_eval(input);
+            match expected {
+                Some(expected) => assert_integer(&evaluated, *expected),
+                None => assert!(
+                    matches!(evaluated, Object::Null),
+                    "Expected Null, got {:?}",
+                    evaluated
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_return_statements() {
+        let tests = [
+            ("return 10;", 10),
+            ("return 10; 9;", 10),
+            ("return 2 * 5; 9;", 10),
+            ("9; return 2 * 5; 9;", 10),
+            (
+                "if (10 > 1) {
+                    if (10 > 1) {
+                        return 10;
+                    }
+                    return 1;
+                }",
+                10,
+            ),
+            // A `return` inside an `if` used as a value leaves the function
+            (
+                "let f = fn() { let x = if (true) { return 1; }; 2 }; f()",
+                1,
+            ),
+            ("fn(n) { 1 + if (n) { return 2; } }(true)", 2),
+            ("fn() { -if (true) { return 3; }; 0 }()", 3),
+            (
+                "let id = fn(x) { x }; fn() { id(if (true) { return 4; }); 0 }()",
+                4,
+            ),
+            ("fn() { [1, if (true) { return 5; }]; 0 }()", 5),
+            (r#"fn() { {"a": if (true) { return 6; }}; 0 }()"#, 6),
+            ("fn() { [1][if (true) { return 0; }]; 9 }()", 0),
+            (
+                "fn() { if (if (true) { return 7; }) { 1 } else { 2 } }()",
+                7,
+            ),
+            ("fn() { let x = 1; x += if (true) { return 8; }; x }()", 8),
+            ("fn() { false || if (true) { return 9; }; 0 }()", 9),
+            ("let x = if (true) { return 10; }; 11", 10),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_integer(&test_eval(input), *expected);
+        }
+    }
+
+    #[test]
+    fn test_error_handling() {
+        let tests = [
+            ("5 + true;", "type mismatch: INTEGER + BOOLEAN"),
+            ("5 + true; 5;", "type mismatch: INTEGER + BOOLEAN"),
+            ("-true", "unknown operator: -BOOLEAN"),
+            ("true + false;", "unknown operator: BOOLEAN + BOOLEAN"),
+            ("5; true + false; 5", "unknown operator: BOOLEAN + BOOLEAN"),
+            (
+                "if (10 > 1) { true + false; }",
+                "unknown operator: BOOLEAN + BOOLEAN",
+            ),
+            (
+                "if (10 > 1) {
+                    if (10 > 1) {
+                        return true + false;
+                    }
+                    return 1;
+                }",
+                "unknown operator: BOOLEAN + BOOLEAN",
+            ),
+            ("foobar", "identifier not found: foobar"),
+            ("10 / (5 - 5)", "division by zero"),
+            (
+                "9223372036854775807 + 1",
+                "integer overflow: 9223372036854775807 + 1",
+            ),
+            (
+                "--9223372036854775808",
+                "integer overflow: --9223372036854775808",
+            ),
+            (
+                "-9223372036854775808 / -1",
+                "integer overflow: -9223372036854775808 / -1",
+            ),
+            ("5(1)", "not a function: INTEGER"),
+            (r#""Hello" - "World""#, "unknown operator: STRING - STRING"),
+            (r#""a" + 1"#, "type mismatch: STRING + INTEGER"),
+            ("1.5 + true", "type mismatch: FLOAT + BOOLEAN"),
+            ("5 % 0", "division by zero"),
+            ("false || missing", "identifier not found: missing"),
+            ("y = 1", "identifier not found: y"),
+            (
+                "let s = \"a\"; s -= \"b\"",
+                "unknown operator: STRING - STRING",
+            ),
+            ("-\"a\"", "unknown operator: -STRING"),
+            ("{fn(x) { x }: 1}", "unusable as hash key: FUNCTION"),
+            ("{1: 2}[[1]]", "unusable as hash key: ARRAY"),
+            ("{}[1.5]", "unusable as hash key: FLOAT"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match test_eval(input) {
+                Object::Error(error) => assert_eq!(error.message, *expected),
+                evaluated => panic!("Expected Error({}), got {:?}", expected, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_eval_float_expression() {
+        let tests = [
+            ("2.5", 2.5),
+            ("-0.5", -0.5),
+            ("0.1 + 0.2", 0.1 + 0.2),
+            ("1.5 * 2", 3.0),
+            ("3 / 2.0", 1.5),
+            ("10 - 0.25 * 4", 9.0),
+            (
+                "let km = fn(miles) { miles * 1.609344 }; km(26.2)",
+                26.2 * 1.609344,
+            ),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match test_eval(input) {
+                Object::Float(value) => assert_eq!(value, *expected, "input: {}", input),
+                evaluated => panic!("Expected Float({}), got {:?}", expected, evaluated),
+            }
+        }
+
+        let tests = [
+            ("1.0 / 0", "inf"),
+            ("-1 / 0.0", "-inf"),
+            ("0.0 / 0.0", "NaN"),
+            ("2.0 * 3", "6.0"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_eq!(test_eval(input).to_string(), *expected, "input: {}", input);
+        }
+
+        let tests = [
+            ("1 == 1.0", true),
+            ("0.5 < 1", true),
+            ("2 > 2.5", false),
+            ("0.0 / 0.0 == 0.0 / 0.0", false),
+            ("0.1 + 0.2 != 0.3", true),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match test_eval(input) {
+                Object::Boolean(value) => assert_eq!(value, *expected, "input: {}", input),
+                evaluated => panic!("Expected Boolean, got {:?}", evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_logical_operators_short_circuit() {
+        // The right side would fail if it were evaluated
+        let tests = [
+            ("false && missing", false),
+            ("true || missing", true),
+            ("let n = 0; n != 0 && 10 / n > 1", false),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_boolean(&test_eval(input), *expected);
+        }
+
+        let input = "let calls = 0;
+            let touch = fn() { calls += 1; true };
+            false && touch();
+            true || touch();
+            true && touch();
+            calls";
+        assert_integer(&test_eval(input), 1);
+    }
+
+    #[test]
+    fn test_assignment() {
+        let tests = [
+            ("let x = 1; x = 2; x", 2),
+            ("let x = 1; x = x + 1", 2),
+            ("let x = 10; x += 5; x -= 3; x *= 2; x /= 4; x", 6),
+            ("let a = 1; let b = 2; a = b = 7; a + b", 14),
+            // Assigns in the scope that defined the name, not a new local one
+            ("let n = 0; let inc = fn() { n += 1 }; inc(); inc(); n", 2),
+            (
+                "let counter = fn() { let c = 0; fn() { c += 1 } }; let next = counter(); next(); next()",
+                2,
+            ),
+            ("let x = 1; let f = fn(x) { x = 5; x }; f(2) + x", 6),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_integer(&test_eval(input), *expected);
+        }
+
+        match test_eval("let x = 1; x += 0.5; x") {
+            Object::Float(value) => assert_eq!(value, 1.5),
+            evaluated => panic!("Expected Float, got {:?}", evaluated),
+        }
+    }
+
+    #[test]
+    fn test_array_literals() {
+        match test_eval("[1, 2 * 2, 3 + 3]") {
+            Object::Array(elements) => {
+                assert_eq!(elements.len(), 3);
+                assert_integer(&elements[0], 1);
+                assert_integer(&elements[1], 4);
+                assert_integer(&elements[2], 6);
+            }
+            evaluated => panic!("Expected Array, got {:?}", evaluated),
+        }
+
+        assert_eq!(
+            test_eval(r#"[1, "two", [3.0, true], []]"#).to_string(),
+            r#"[1, "two", [3.0, true], []]"#
+        );
+    }
+
+    #[test]
+    fn test_array_index_expressions() {
+        let tests = [
+            ("[1, 2, 3][0]", 1),
+            ("[1, 2, 3][1]", 2),
+            ("[1, 2, 3][2]", 3),
+            ("let i = 0; [1][i]", 1),
+            ("[1, 2, 3][1 + 1]", 3),
+            ("let xs = [1, 2, 3]; xs[2]", 3),
+            ("let xs = [1, 2, 3]; xs[0] + xs[1] + xs[2]", 6),
+            ("let xs = [1, 2, 3]; let i = xs[0]; xs[i]", 2),
+            ("[[1, 2], [3, 4]][1][0]", 3),
+            ("let xs = [1, 2 * 2, fn(x) { x }]; xs[2](xs[1])", 4),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_integer(&test_eval(input), *expected);
+        }
+    }
+
+    #[test]
+    fn test_hash_literals() {
+        let input = r#"let two = "two";
+        {
+            "one": 10 - 9,
+            two: 1 + 1,
+            "thr" + "ee": 6 / 2,
+            4: 4,
+            true: 5,
+            false: 6
+        }"#;
+
+        match test_eval(input) {
+            Object::Hash(pairs) => {
+                let expected = [
+                    (HashKey::String("one".to_string()), 1),
+                    (HashKey::String("two".to_string()), 2),
+                    (HashKey::String("three".to_string()), 3),
+                    (HashKey::Integer(4), 4),
+                    (HashKey::Boolean(true), 5),
+                    (HashKey::Boolean(false), 6),
+                ];
+                assert_eq!(pairs.len(), expected.len());
+                for (key, value) in expected.iter() {
+                    assert_integer(&pairs[key], *value);
+                }
+            }
+            evaluated => panic!("Expected Hash, got {:?}", evaluated),
+        }
+
+        // Printed in key order, whatever order the source used
+        assert_eq!(
+            test_eval(r#"{"b": [1], 2: "x", true: {}, "a": 1, 1: 2, "b": false}"#).to_string(),
+            r#"{1: 2, 2: "x", true: {}, "a": 1, "b": false}"#
+        );
+    }
+
+    #[test]
+    fn test_hash_index_expressions() {
+        let tests = [
+            (r#"{"foo": 5}["foo"]"#, Some(5)),
+            (r#"{"foo": 5}["bar"]"#, None),
+            (r#"let key = "foo"; {"foo": 5}[key]"#, Some(5)),
+            (r#"{}["foo"]"#, None),
+            ("{5: 5}[5]", Some(5)),
+            ("{true: 5}[true]", Some(5)),
+            ("{false: 5}[false]", Some(5)),
+            (r#"{1: 5}["1"]"#, None),
+            ("{1: 5}[true]", None),
+            (r#"let h = {"f": fn(x) { x * 2 }}; h["f"](3)"#, Some(6)),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match expected {
+                Some(expected) => assert_integer(&test_eval(input), *expected),
+                None => assert!(matches!(test_eval(input), Object::Null), "input: {}", input),
+            }
+        }
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        let tests = [
+            (r#"len("")"#, "0"),
+            (r#"len("four")"#, "4"),
+            (r#"len("héllo")"#, "5"),
+            ("len([1, 2, 3])", "3"),
+            (r#"len({"a": 1, "b": 2})"#, "2"),
+            ("first([1, 2, 3])", "1"),
+            ("first([])", "null"),
+            ("last([1, 2, 3])", "3"),
+            ("last([])", "null"),
+            ("rest([1, 2, 3])", "[2, 3]"),
+            ("rest(rest([1]))", "null"),
+            ("rest([])", "null"),
+            ("push([], 1)", "[1]"),
+            (
+                "let xs = [1]; let ys = push(xs, 2); [xs, ys]",
+                "[[1], [1, 2]]",
+            ),
+            (r#"puts("hello", 1)"#, "null"),
+            ("let len = fn(x) { 42 }; len([])", "42"),
+            ("let f = first; f([7])", "7"),
+            ("len", "builtin function len"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_eq!(test_eval(input).to_string(), *expected, "input: {}", input);
+        }
+
+        let tests = [
+            ("len(1)", "argument to `len` not supported, got INTEGER"),
+            (
+                r#"len("one", "two")"#,
+                "wrong number of arguments: want=1, got=2",
+            ),
+            ("first()", "wrong number of arguments: want=1, got=0"),
+            (
+                r#"first("ab")"#,
+                "argument to `first` not supported, got STRING",
+            ),
+            (
+                "last(true)",
+                "argument to `last` not supported, got BOOLEAN",
+            ),
+            ("rest({})", "argument to `rest` not supported, got HASH"),
+            (
+                "push(1, 1)",
+                "argument to `push` not supported, got INTEGER",
+            ),
+            ("push([])", "wrong number of arguments: want=2, got=1"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match test_eval(input) {
+                Object::Error(error) => {
+                    assert_eq!(error.message, *expected);
+                    assert_eq!((error.span.start, error.span.end), (0, input.len()));
+                }
+                evaluated => panic!("Expected Error({}), got {:?}", expected, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_expressions() {
+        let tests = [
+            (r#""Hello World!""#, "Hello World!"),
+            (r#""Hello" + " " + "World!""#, "Hello World!"),
+            (
+                r#"let greet = fn(name) { "hi " + name }; greet("\u{1F600}")"#,
+                "hi 😀",
+            ),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match test_eval(input) {
+                Object::String(value) => assert_eq!(value, *expected),
+                evaluated => panic!("Expected String({}), got {:?}", expected, evaluated),
+            }
+        }
+
+        let tests = [
+            (r#""a" == "a""#, true),
+            (r#""a" == "b""#, false),
+            (r#""a" != "b""#, true),
+            (r#""a" + "b" == "ab""#, true),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match test_eval(input) {
+                Object::Boolean(value) => assert_eq!(value, *expected, "input: {}", input),
+                evaluated => panic!("Expected Boolean, got {:?}", evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_function_object() {
+        match test_eval("fn(x) { x + 2; };") {
+            Object::Function(function) => {
+                assert_eq!(function.parameters.len(), 1);
+                assert_eq!(function.parameters[0].name, "x");
+                assert_eq!(function.body.statements.len(), 1);
+                assert_eq!(function.to_string(), "fn(x) { (x + 2); }");
+            }
+            evaluated => panic!("Expected Function, got {:?}", evaluated),
+        }
+    }
+
+    #[test]
+    fn test_let_statements() {
+        let tests = [
+            ("let a = 5; a;", 5),
+            ("let a = 5 * 5; a;", 25),
+            ("let a = 5; let b = a; b;", 5),
+            ("let a = 5; let b = a; let c = a + b + 5; c;", 15),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_integer(&test_eval(input), *expected);
+        }
+    }
+
+    #[test]
+    fn test_function_application() {
+        let tests = [
+            ("let identity = fn(x) { x; }; identity(5);", 5),
+            ("let identity = fn(x) { return x; }; identity(5);", 5),
+            ("let double = fn(x) { x * 2; }; double(5);", 10),
+            ("let add = fn(x, y) { x + y; }; add(5, 5);", 10),
+            ("let add = fn(x, y) { x + y; }; add(5 + 5, add(5, 5));", 20),
+            ("fn(x) { x; }(5)", 5),
+            ("let f = fn() { return 1; 2 }; f() + 10;", 11),
+        ];
+
+        for (input, expected) in tests.iter() {
+            assert_integer(&test_eval(input), *expected);
+        }
+    }
+
+    #[test]
+    fn test_wrong_number_of_arguments() {
+        match test_eval("let add = fn(x, y) { x + y; }; add(1);") {
+            Object::Error(error) => {
+                assert_eq!(error.message, "wrong number of arguments: want=2, got=1")
+            }
+            evaluated => panic!("Expected Error, got {:?}", evaluated),
+        }
+    }
+
+    #[test]
+    fn test_closures() {
+        let input = "
+        let newAdder = fn(x) {
+            fn(y) { x + y };
+        };
+
+        let addTwo = newAdder(2);
+        addTwo(2);";
+
+        assert_integer(&test_eval(input), 4);
+    }
+
+    #[test]
+    fn test_parameters_shadow_outer_bindings() {
+        let input = "let x = 10; let f = fn(x) { x * 2 }; f(3) + x;";
+
+        assert_integer(&test_eval(input), 16);
+    }
+
+    #[test]
+    fn test_recursive_function() {
+        let input = "
+        let countdown = fn(n) {
+            if (n == 0) { return 0; }
+            countdown(n - 1)
+        };
+        let wrapper = fn() {
+            let inner = fn(n) { if (n == 0) { 42 } else { inner(n - 1) } };
+            inner(5)
+        };
+        countdown(10) + wrapper();";
+
+        assert_integer(&test_eval(input), 42);
+    }
+
+    #[test]
+    fn test_recursive_function_does_not_leak_environment() {
+        let env = Rc::new(RefCell::new(Environment::new()));
+        test_eval_in(
+            "let f = fn(n) { if (n == 0) { 0 } else { f(n - 1) } }; f(3);",
+            &env,
+        );
+
+        let weak = Rc::downgrade(&env);
+        drop(env);
+        assert!(
+            weak.upgrade().is_none(),
+            "environment was kept alive by a cycle"
+        );
+    }
+
+    #[test]
+    fn test_returned_closure_keeps_its_environment_alive() {
+        let input = "
+        let make = fn() {
+            let counter = fn(n) { if (n == 0) { 7 } else { counter(n - 1) } };
+            counter
+        };
+        let counter = make();
+        counter(3);";
+
+        assert_integer(&test_eval(input), 7);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // Runs on the test thread's own small stack, deep recursion must not overflow it
+        let env = Rc::new(RefCell::new(Environment::new()));
+        let countdown = "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } };
+            let nested = fn(n) {
+                if (n == 0) { 0 } else { if (true) { if (true) { if (true) {
+                    1 + nested(n - 1)
+                } } } }
+            };";
+        test_eval_in(countdown, &env);
+
+        assert_integer(&test_eval_in("f(3000)", &env), 3000);
+        assert_integer(&test_eval_in("nested(1000)", &env), 1000);
+
+        for input in ["let g = fn(n) { g(n + 1) }; g(0)", "f(5000)"] {
+            match test_eval_in(input, &env) {
+                Object::Error(error) => {
+                    assert_eq!(error.message, "maximum evaluation depth exceeded")
+                }
+                evaluated => panic!("Expected Error, got {:?}", evaluated),
+            }
+        }
+
+        // The depth unwinds with the error, so later calls are unaffected
+        assert_integer(&test_eval_in("f(3000)", &env), 3000);
+    }
+
+    #[test]
+    fn test_error_spans() {
+        let tests = [
+            ("let x = 1;\nx + true", "x + true"),
+            ("-true", "-true"),
+            ("1 + foo", "foo"),
+            ("let f = fn(x) { x };\nf(1, 2)", "f(1, 2)"),
+            ("if (true) { 5(1) }", "5"),
+            ("fn() { 1 / 0 }()", "1 / 0"),
+            ("let xs = [1, 2];\nxs[1 + 1]", "1 + 1"),
+            ("[1][true]", "[1][true]"),
+            ("{\"a\": 1, [2]: 3}", "[2]"),
+            ("let h = {};\nh[fn() { 1 }]", "fn() { 1 }"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match test_eval(input) {
+                Object::Error(error) => {
+                    assert_eq!(&input[error.span.start..error.span.end], *expected)
+                }
+                evaluated => panic!("Expected Error, got {:?}", evaluated),
+            }
+        }
+    }
+}
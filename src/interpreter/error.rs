@@ -0,0 +1,30 @@
+use std::error;
+use std::fmt;
+
+use crate::object::RuntimeError;
+use crate::parser::ParseError;
+
+/// Why `Interpreter::eval_str` failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The source did not parse, nothing was evaluated
+    Parse(Vec<ParseError>),
+    /// Evaluation stopped at a runtime error
+    Runtime(RuntimeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(errors) => {
+                let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+            Error::Runtime(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// Display already prints the underlying errors, so they are not also
+// reported as the source, which would repeat them in error chains
+impl error::Error for Error {}
@@ -0,0 +1,186 @@
+mod error;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::ast;
+use crate::eval::eval_program;
+use crate::object::{Builtin, Env, Environment, IntoObject, Object, RuntimeError};
+use crate::parser::parse;
+
+pub use error::Error;
+
+/// Runs Junkie source from a Rust program. Globals and functions the host
+/// provides, as well as the `let` bindings of every script evaluated so far,
+/// stay visible to later calls of `eval_str`
+///
+/// ```
+/// use junkie::{FromObject, Interpreter};
+///
+/// let mut interpreter = Interpreter::new();
+/// interpreter.set_global("limit", 10);
+/// interpreter.register_fn("double", |args| match args {
+///     [n] => Ok(i64::from_object(n)? * 2),
+///     _ => Err(format!("wrong number of arguments: want=1, got={}", args.len())),
+/// });
+///
+/// let result = interpreter.eval_str("double(limit) + 1").unwrap();
+/// assert_eq!(i64::from_object(&result), Ok(21));
+/// ```
+#[derive(Debug)]
+pub struct Interpreter {
+    env: Env,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        return Interpreter {
+            env: Rc::new(RefCell::new(Environment::new())),
+        };
+    }
+
+    /// Parses and evaluates `source`, returning the value of its last statement
+    pub fn eval_str(&mut self, source: &str) -> Result<Object, Error> {
+        let program = parse(source).map_err(Error::Parse)?;
+        return self.eval_program(&program).map_err(Error::Runtime);
+    }
+
+    /// Evaluates an already parsed program, for hosts that parse it themselves
+    pub fn eval_program(&mut self, program: &ast::Program) -> Result<Object, RuntimeError> {
+        match eval_program(program, &self.env) {
+            Object::Error(error) => Err(error),
+            result => Ok(result),
+        }
+    }
+
+    /// Binds `name` to `value` in the global scope, replacing any previous binding
+    pub fn set_global(&mut self, name: &str, value: impl IntoObject) {
+        self.env
+            .borrow_mut()
+            .set(name.to_string(), value.into_object());
+    }
+
+    /// Makes `function` callable from scripts as `name`. It receives the
+    /// evaluated arguments, and an `Err` is raised as a runtime error at the call
+    pub fn register_fn<F, T>(&mut self, name: &str, function: F)
+    where
+        F: Fn(&[Object]) -> Result<T, String> + 'static,
+        T: IntoObject,
+    {
+        let builtin = Builtin::new(name, move |arguments| {
+            function(arguments).map(IntoObject::into_object)
+        });
+        self.set_global(name, Object::Builtin(builtin));
+    }
+}
+
+/// Scripts easily leave functions referring to the very scopes that hold them,
+/// such as a closure bound to a global, so those are cleared explicitly
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        self.env.borrow_mut().clear();
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        return Interpreter::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::FromObject;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_eval_str_keeps_bindings() {
+        let mut interpreter = Interpreter::new();
+
+        assert!(matches!(
+            interpreter.eval_str("let x = 2;"),
+            Ok(Object::Null)
+        ));
+        let result = interpreter.eval_str("x * 21").unwrap();
+        assert_eq!(i64::from_object(&result), Ok(42));
+    }
+
+    #[test]
+    fn test_set_global() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("name", "junkie");
+        interpreter.set_global("scores", vec![1.5, 2.5]);
+        interpreter.set_global("len", 3);
+
+        let result = interpreter
+            .eval_str(r#""hi " + name + " " + len"#)
+            .unwrap_err();
+        assert_eq!(result.to_string(), "type mismatch: STRING + INTEGER");
+
+        let result = interpreter.eval_str("scores[0] + scores[1]").unwrap();
+        assert_eq!(f64::from_object(&result), Ok(4.0));
+    }
+
+    #[test]
+    fn test_register_fn() {
+        let mut interpreter = Interpreter::new();
+        let prefix = String::from("> ");
+        interpreter.register_fn("quote", move |args| match args {
+            [line] => Ok(prefix.clone() + &String::from_object(line)?),
+            _ => Err("quote takes one argument".to_string()),
+        });
+        interpreter.register_fn("noop", |_| Ok(()));
+
+        let result = interpreter.eval_str(r#"quote("a") + quote("b")"#).unwrap();
+        assert_eq!(result.to_string(), "> a> b");
+        assert!(matches!(
+            interpreter.eval_str("noop(1, 2)"),
+            Ok(Object::Null)
+        ));
+
+        match interpreter.eval_str("let q = quote;\nq(1)") {
+            Err(Error::Runtime(error)) => {
+                assert_eq!(error.message, "expected STRING, got INTEGER");
+                assert_eq!((error.span.start, error.span.end), (15, 19));
+            }
+            other => panic!("Expected a runtime error, got {:?}", other),
+        }
+
+        let error = interpreter.eval_str("quote()").unwrap_err();
+        assert_eq!(error.to_string(), "quote takes one argument");
+        assert!(std::error::Error::source(&error).is_none());
+    }
+
+    #[test]
+    fn test_drop_frees_closures() {
+        let scripts = [
+            "let adder = fn(x) { fn(y) { x + y } }; let add2 = adder(2); add2",
+            "let counter = fn() { let c = 0; fn() { c += 1 } }; let next = counter(); next",
+            "let make = fn() { let fs = [fn() { fs }]; fs[0] }; let f = make(); f",
+            r#"let make = fn() { let fs = {"f": fn() { fs }}; fs["f"] }; make()"#,
+        ];
+
+        for script in scripts.iter() {
+            let mut interpreter = Interpreter::new();
+            let env = match interpreter.eval_str(script) {
+                Ok(Object::Function(function)) => Rc::downgrade(&function.env.upgrade().unwrap()),
+                other => panic!("Expected a function, got {:?}", other),
+            };
+
+            assert!(env.upgrade().is_some(), "{}", script);
+            drop(interpreter);
+            assert!(env.upgrade().is_none(), "{}", script);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut interpreter = Interpreter::new();
+
+        match interpreter.eval_str("let = 1; let x 2;") {
+            Err(Error::Parse(errors)) => assert_eq!(errors.len(), 2),
+            other => panic!("Expected parse errors, got {:?}", other),
+        }
+    }
+}
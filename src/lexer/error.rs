@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::token::Span;
+
+/// Malformed source the lexer could not turn into a valid token
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexError {
+    /// A string literal reached the end of input before its closing `"`
+    UnterminatedString { span: Span },
+    /// A `/*` comment reached the end of input before its closing `*/`
+    UnterminatedComment { span: Span },
+    /// An integer literal too large for a signed 64-bit integer
+    IntegerOutOfRange { literal: String, span: Span },
+    /// An integer literal without digits, or with digits its base does not allow
+    InvalidInteger { literal: String, span: Span },
+    /// A float literal too large for a 64-bit float
+    FloatOutOfRange { literal: String, span: Span },
+    /// A float literal starting with its point, like `.5`
+    MissingIntegerPart { literal: String, span: Span },
+    /// A backslash followed by a character that is not a known escape
+    InvalidEscape { escape: String, span: Span },
+    /// A `\u{...}` escape that does not name a unicode scalar value
+    InvalidUnicodeEscape { escape: String, span: Span },
+}
+
+impl LexError {
+    /// Location in the source the error points at
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::UnterminatedString { span } => *span,
+            LexError::UnterminatedComment { span } => *span,
+            LexError::IntegerOutOfRange { span, .. } => *span,
+            LexError::InvalidInteger { span, .. } => *span,
+            LexError::FloatOutOfRange { span, .. } => *span,
+            LexError::MissingIntegerPart { span, .. } => *span,
+            LexError::InvalidEscape { span, .. } => *span,
+            LexError::InvalidUnicodeEscape { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexError::UnterminatedString { .. } => {
+                write!(f, "unterminated string, expected `\"` before end of input")
+            }
+            LexError::UnterminatedComment { .. } => {
+                write!(
+                    f,
+                    "unterminated block comment, expected `*/` before end of input"
+                )
+            }
+            LexError::IntegerOutOfRange { literal, .. } => {
+                write!(f, "integer literal `{}` is out of range", literal)
+            }
+            LexError::InvalidInteger { literal, .. } => {
+                write!(f, "invalid integer literal `{}`", literal)
+            }
+            LexError::FloatOutOfRange { literal, .. } => {
+                write!(f, "float literal `{}` is out of range", literal)
+            }
+            LexError::MissingIntegerPart { literal, .. } => {
+                write!(
+                    f,
+                    "float literal `{}` needs a digit before the point",
+                    literal
+                )
+            }
+            LexError::InvalidEscape { escape, .. } => {
+                write!(f, "unknown escape sequence `{}`", escape)
+            }
+            LexError::InvalidUnicodeEscape { escape, .. } => {
+                write!(f, "invalid unicode escape `{}`", escape)
+            }
+        }
+    }
+}
+
+impl Error for LexError {}
@@ -0,0 +1,1247 @@
+#![allow(dead_code)]
+
+mod error;
+
+use std::borrow::Cow;
+use std::iter::FusedIterator;
+use std::num::IntErrorKind;
+
+use crate::token::Span;
+use crate::token::Token;
+use crate::token::TokenType;
+use crate::utils::{is_digit, is_identifier_continue, is_identifier_start};
+
+pub use error::LexError;
+
+/// Turns source text into tokens
+///
+/// The lexer walks the input once with a byte cursor, so tokenising is linear
+/// in the size of the input. Tokens borrow their literals from the input, so
+/// only string literals, which carry their decoded value, allocate
+///
+/// Comments are skipped like whitespace, unless the lexer is built
+/// `with_comments`, in which case they come out as `COMMENT` tokens
+#[derive(Debug, Clone)]
+pub struct Lexer<'src> {
+    input: &'src str,
+    current_char: Option<char>,
+    emit_comments: bool,
+
+    /// Byte offset, line and column of the current character, for spans
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'src> Lexer<'src> {
+    /// Returns a new lexer object positioned on the first character of input
+    pub fn new(input: &'src str) -> Lexer<'src> {
+        Lexer {
+            input,
+            current_char: input.chars().next(),
+            emit_comments: false,
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Returns a lexer positioned at byte `offset` of `input`, so that spans
+    /// stay relative to the whole input when only its tail is lexed.
+    /// Panics if `offset` is not on a char boundary
+    pub fn resume(input: &'src str, offset: usize) -> Lexer<'src> {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Lexer {
+            input,
+            current_char: input[offset..].chars().next(),
+            emit_comments: false,
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Emits comments as `COMMENT` tokens instead of skipping them, for tools
+    /// that need to preserve them
+    pub fn with_comments(mut self) -> Lexer<'src> {
+        self.emit_comments = true;
+        return self;
+    }
+
+    fn get_current_char(&self) -> Option<char> {
+        self.current_char
+    }
+
+    /// Moves the cursor past the current character
+    fn advance(&mut self) {
+        if let Some(ch) = self.current_char {
+            self.offset += ch.len_utf8();
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.current_char = self.input[self.offset..].chars().next();
+    }
+
+    fn is_whitespace(&self, ch: char) -> bool {
+        return ch.is_whitespace();
+    }
+
+    /// Skips whitespace, and comments unless they are emitted as tokens
+    ///
+    /// Stops in front of an unterminated block comment, so that `next_token`
+    /// reports it
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.get_current_char() {
+            if self.is_whitespace(ch) {
+                self.advance();
+                continue;
+            }
+            if self.emit_comments || !self.at_comment() {
+                break;
+            }
+
+            let checkpoint = self.clone();
+            if let TokenType::ERROR(_) = self.read_comment() {
+                *self = checkpoint;
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    /// Whether a `//` or `/*` comment starts at the current character
+    fn at_comment(&self) -> bool {
+        return self.current_char == Some('/') && matches!(self.peek(), Some('/' | '*'));
+    }
+
+    /// Reads a `//` line comment or a `/* */` block comment. Block comments nest
+    ///
+    /// Leaves the lexer on the last character of the comment, which for a line
+    /// comment is the one before the newline
+    fn read_comment(&mut self) -> TokenType {
+        let start = self.here();
+        self.advance();
+
+        if self.current_char == Some('/') {
+            while !matches!(self.peek(), Some('\n') | None) {
+                self.advance();
+            }
+            return TokenType::COMMENT;
+        }
+
+        let mut depth = 1;
+        while depth > 0 {
+            self.advance();
+            match (self.current_char, self.peek()) {
+                (Some('/'), Some('*')) => {
+                    self.advance();
+                    depth += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    self.advance();
+                    depth -= 1;
+                }
+                (Some(_), None) | (None, _) => {
+                    let span = start.to(self.here());
+                    return TokenType::ERROR(LexError::UnterminatedComment { span });
+                }
+                _ => {}
+            }
+        }
+        return TokenType::COMMENT;
+    }
+
+    /// Returns the character after the current one without consuming anything
+    fn peek(&self) -> Option<char> {
+        let ch = self.current_char?;
+        return self.input[self.offset + ch.len_utf8()..].chars().next();
+    }
+
+    /// Returns the character two after the current one without consuming anything
+    fn peek_second(&self) -> Option<char> {
+        let ch = self.current_char?;
+        return self.input[self.offset + ch.len_utf8()..].chars().nth(1);
+    }
+
+    /// Reads an identifier starting at the current character
+    ///
+    /// Leaves the lexer on the last character of the identifier
+    fn read_identifier(&mut self) -> &'src str {
+        return self.read_while(is_identifier_continue);
+    }
+
+    /// Reads an integer literal starting at the current character: decimal, or
+    /// hexadecimal, octal or binary behind a `0x`, `0o` or `0b` prefix. `_` may
+    /// separate digits
+    ///
+    /// Leaves the lexer on the last character of the literal
+    fn read_number(&mut self) -> TokenType {
+        let start = self.here();
+        let radix = match (self.current_char, self.peek()) {
+            (Some('0'), Some('x')) => 16,
+            (Some('0'), Some('o')) => 8,
+            (Some('0'), Some('b')) => 2,
+            _ => 10,
+        };
+
+        let digits = if radix == 10 {
+            let digits = self.read_while(is_digit_or_separator);
+            if self.at_fraction() || self.at_exponent() {
+                return self.read_float(start);
+            }
+            digits
+        } else {
+            // Take in every alphanumeric, so `0b102` is one bad literal rather than `0b10` and `2`
+            self.advance();
+            match self.peek() {
+                Some(next) if is_identifier_continue(next) => {
+                    self.advance();
+                    self.read_while(is_identifier_continue)
+                }
+                _ => "",
+            }
+        };
+
+        let span = start.to(self.here());
+        let literal = &self.input[span.start..span.end];
+        let digits: Cow<str> = if digits.contains('_') {
+            Cow::Owned(digits.replace('_', ""))
+        } else {
+            Cow::Borrowed(digits)
+        };
+
+        match i64::from_str_radix(&digits, radix) {
+            Ok(value) => TokenType::INT(value),
+            Err(error) if *error.kind() == IntErrorKind::PosOverflow => {
+                TokenType::ERROR(LexError::IntegerOutOfRange {
+                    literal: literal.to_string(),
+                    span,
+                })
+            }
+            Err(_) => TokenType::ERROR(LexError::InvalidInteger {
+                literal: literal.to_string(),
+                span,
+            }),
+        }
+    }
+
+    /// Reads the current character and every following one matching `accept`
+    fn read_while(&mut self, accept: fn(char) -> bool) -> &'src str {
+        let start = self.offset;
+
+        while let Some(next) = self.peek() {
+            if !accept(next) {
+                break;
+            }
+            self.advance();
+        }
+
+        let end = self.offset + self.current_char.map_or(0, char::len_utf8);
+        return &self.input[start..end];
+    }
+
+    /// Span of the current character
+    fn here(&self) -> Span {
+        return Span {
+            start: self.offset,
+            end: self.offset + self.current_char.map_or(0, char::len_utf8),
+            line: self.line,
+            column: self.column,
+        };
+    }
+
+    /// Whether a `.` followed by a digit comes after the current character
+    fn at_fraction(&self) -> bool {
+        return self.peek() == Some('.') && self.peek_second().is_some_and(is_digit);
+    }
+
+    /// Whether an `e` exponent with digits comes after the current character
+    fn at_exponent(&self) -> bool {
+        if !matches!(self.peek(), Some('e' | 'E')) {
+            return false;
+        }
+
+        let rest = &self.input[self.here().end + 1..];
+        let digits = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+        return digits.starts_with(|ch: char| is_digit(ch));
+    }
+
+    /// Reads the fraction and exponent of a float literal whose integer part
+    /// has been read already, `start` being its first character
+    ///
+    /// A float needs digits on both sides of the point, so `.5` and `5.` are
+    /// not floats. An `e` only starts an exponent when digits follow it
+    ///
+    /// Leaves the lexer on the last character of the literal
+    fn read_float(&mut self, start: Span) -> TokenType {
+        if self.at_fraction() {
+            self.advance();
+            self.advance();
+            self.read_while(is_digit_or_separator);
+        }
+
+        self.read_exponent();
+
+        let span = start.to(self.here());
+        let literal = &self.input[span.start..span.end];
+
+        match literal.replace('_', "").parse::<f64>() {
+            Ok(value) if value.is_finite() => TokenType::FLOAT(value),
+            _ => TokenType::ERROR(LexError::FloatOutOfRange {
+                literal: literal.to_string(),
+                span,
+            }),
+        }
+    }
+
+    /// Reads an exponent like `e10` or `E-3` if one follows the current character
+    fn read_exponent(&mut self) {
+        if !self.at_exponent() {
+            return;
+        }
+
+        self.advance();
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.advance();
+        }
+        self.advance();
+        self.read_while(is_digit_or_separator);
+    }
+
+    /// Reads what looks like a float missing the digits before its point, `.5`
+    ///
+    /// Leaves the lexer on the last character of the literal
+    fn read_leading_point(&mut self) -> TokenType {
+        let start = self.here();
+        self.advance();
+        self.read_while(is_digit_or_separator);
+        self.read_exponent();
+
+        let span = start.to(self.here());
+        return TokenType::ERROR(LexError::MissingIntegerPart {
+            literal: self.input[span.start..span.end].to_string(),
+            span,
+        });
+    }
+
+    /// Reads a string literal starting at the opening `"`, decoding escapes
+    ///
+    /// Leaves the lexer on the closing `"`, or on the last character of the
+    /// input if the string is never closed
+    fn read_string(&mut self) -> TokenType {
+        let start = self.here();
+        let mut value = String::new();
+        let mut error = None;
+
+        loop {
+            match self.peek() {
+                None => {
+                    let span = start.to(self.here());
+                    return TokenType::ERROR(LexError::UnterminatedString { span });
+                }
+                Some('"') => break,
+                Some('\\') => {
+                    self.advance();
+                    match self.read_escape() {
+                        Ok(ch) => value.push(ch),
+                        Err(err) => {
+                            error.get_or_insert(err);
+                        }
+                    }
+                }
+                Some(ch) => {
+                    self.advance();
+                    value.push(ch);
+                }
+            }
+        }
+
+        self.advance();
+        match error {
+            Some(error) => TokenType::ERROR(error),
+            None => TokenType::STRING(value),
+        }
+    }
+
+    /// Reads an escape sequence starting at the backslash
+    ///
+    /// Leaves the lexer on the last character of the escape
+    fn read_escape(&mut self) -> Result<char, LexError> {
+        let start = self.here();
+
+        let ch = match self.peek() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('u') => {
+                self.advance();
+                return self.read_unicode_escape(start);
+            }
+            Some(_) => {
+                self.advance();
+                let span = start.to(self.here());
+                return Err(LexError::InvalidEscape {
+                    escape: self.input[span.start..span.end].to_string(),
+                    span,
+                });
+            }
+            None => {
+                return Err(LexError::InvalidEscape {
+                    escape: "\\".to_string(),
+                    span: start,
+                })
+            }
+        };
+
+        self.advance();
+        return Ok(ch);
+    }
+
+    /// Reads the `{...}` part of a `\u{...}` escape, `start` being the backslash.
+    /// Accepts one to six hex digits naming a unicode scalar value
+    ///
+    /// Leaves the lexer on the closing `}`
+    fn read_unicode_escape(&mut self, start: Span) -> Result<char, LexError> {
+        let mut closed = false;
+        if self.peek() == Some('{') {
+            self.advance();
+            while let Some(next) = self.peek() {
+                if next.is_ascii_hexdigit() {
+                    self.advance();
+                } else {
+                    if next == '}' {
+                        self.advance();
+                        closed = true;
+                    }
+                    break;
+                }
+            }
+        }
+
+        let span = start.to(self.here());
+        let escape = &self.input[span.start..span.end];
+        let digits = escape.trim_start_matches("\\u{").trim_end_matches('}');
+
+        let ch = if closed && (1..=6).contains(&digits.len()) {
+            u32::from_str_radix(digits, 16)
+                .ok()
+                .and_then(char::from_u32)
+        } else {
+            None
+        };
+
+        return ch.ok_or_else(|| LexError::InvalidUnicodeEscape {
+            escape: escape.to_string(),
+            span,
+        });
+    }
+
+    /// If the character after the current one is `next`, consumes it and
+    /// returns `then`, otherwise returns `otherwise`
+    fn either(&mut self, next: char, then: TokenType, otherwise: TokenType) -> TokenType {
+        if self.peek() == Some(next) {
+            self.advance();
+            return then;
+        }
+        return otherwise;
+    }
+
+    pub fn next_token(&mut self) -> Token<'src> {
+        self.skip_whitespace();
+
+        let (start, line, column) = (self.offset, self.line, self.column);
+
+        let tok_type = match self.get_current_char() {
+            Some('=') => self.either('=', TokenType::EQUAL, TokenType::ASSIGN),
+            Some('!') => self.either('=', TokenType::NOTEQUAL, TokenType::BANG),
+            Some(';') => TokenType::SEMICOLON,
+            Some(':') => TokenType::COLON,
+            Some('(') => TokenType::LPAREN,
+            Some(')') => TokenType::RPAREN,
+            Some(',') => TokenType::COMMA,
+            Some('+') => self.either('=', TokenType::PLUSASSIGN, TokenType::PLUS),
+            Some('{') => TokenType::LBRACE,
+            Some('}') => TokenType::RBRACE,
+            Some('[') => TokenType::LBRACKET,
+            Some(']') => TokenType::RBRACKET,
+            Some('-') => self.either('=', TokenType::MINUSASSIGN, TokenType::MINUS),
+            Some('/') if self.at_comment() => self.read_comment(),
+            Some('/') => self.either('=', TokenType::SLASHASSIGN, TokenType::SLASH),
+            Some('*') => self.either('=', TokenType::ASTERISKASSIGN, TokenType::ASTERISK),
+            Some('%') => TokenType::PERCENT,
+            Some('>') => self.either('=', TokenType::GREATEREQUAL, TokenType::GREATERTHAN),
+            Some('<') => self.either('=', TokenType::LESSEQUAL, TokenType::LESSTHAN),
+            Some('&') => self.either('&', TokenType::AND, TokenType::ILLEGAL),
+            Some('|') => self.either('|', TokenType::OR, TokenType::ILLEGAL),
+            Some('"') => self.read_string(),
+            Some('.') if self.peek().is_some_and(is_digit) => self.read_leading_point(),
+            Some(ch) if is_identifier_start(ch) => match self.read_identifier() {
+                "let" => TokenType::LET,
+                "fn" => TokenType::FUNCTION,
+                "true" => TokenType::TRUE,
+                "false" => TokenType::FALSE,
+                "if" => TokenType::IF,
+                "else" => TokenType::ELSE,
+                "return" => TokenType::RETURN,
+                _ => TokenType::IDENT,
+            },
+            Some(ch) if is_digit(ch) => self.read_number(),
+            Some(_) => TokenType::ILLEGAL,
+            None => TokenType::EOF,
+        };
+
+        self.advance();
+
+        return Token {
+            kind: tok_type,
+            literal: Cow::Borrowed(&self.input[start..self.offset]),
+            span: Span {
+                start,
+                end: self.offset,
+                line,
+                column,
+            },
+        };
+    }
+}
+
+fn is_digit_or_separator(ch: char) -> bool {
+    return is_digit(ch) || ch == '_';
+}
+
+/// Yields every token up to, but not including, EOF
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Token<'src>;
+
+    fn next(&mut self) -> Option<Token<'src>> {
+        let token = self.next_token();
+        if token.kind == TokenType::EOF {
+            return None;
+        }
+        return Some(token);
+    }
+}
+
+// Once at the end of input the lexer keeps returning EOF, so it stays exhausted
+impl FusedIterator for Lexer<'_> {}
+
+/// Whether an integer literal spells out 9223372036854775808, the magnitude of
+/// `i64::MIN`. It is out of range on its own, but not once negated
+pub(crate) fn is_min_magnitude(literal: &str) -> bool {
+    let literal = literal.replace('_', "");
+    let (digits, radix) = match literal.get(..2) {
+        Some("0x") => (&literal[2..], 16),
+        Some("0o") => (&literal[2..], 8),
+        Some("0b") => (&literal[2..], 2),
+        _ => (&literal[..], 10),
+    };
+    return u64::from_str_radix(digits, radix) == Ok(i64::MIN.unsigned_abs());
+}
+
+/// Lexes the whole of `input`, without the trailing EOF token
+pub fn tokenize(input: &str) -> Vec<Token<'_>> {
+    return Lexer::new(input).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_next_token_second() {
+        let input = "let five = 5;
+    let ten = 10;
+
+    let add = fn(x, y) {
+        x + y;
+    }
+
+    let result = add(five, ten);
+    !-/ *5;
+    5 < 10 > 5;
+
+    if (5 < 10) {
+        return true;
+        } else {
+        return false;
+        }
+
+    10 == 10;
+    10 != 9;
+    ";
+
+        let mut lexer = Lexer::new(input);
+
+        let tests = [
+            Token::new_token(TokenType::LET, "let"),
+            Token::new_token(TokenType::IDENT, "five"),
+            Token::new_token(TokenType::ASSIGN, "="),
+            Token::new_token(TokenType::INT(5), "5"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::LET, "let"),
+            Token::new_token(TokenType::IDENT, "ten"),
+            Token::new_token(TokenType::ASSIGN, "="),
+            Token::new_token(TokenType::INT(10), "10"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::LET, "let"),
+            Token::new_token(TokenType::IDENT, "add"),
+            Token::new_token(TokenType::ASSIGN, "="),
+            Token::new_token(TokenType::FUNCTION, "fn"),
+            Token::new_token(TokenType::LPAREN, "("),
+            Token::new_token(TokenType::IDENT, "x"),
+            Token::new_token(TokenType::COMMA, ","),
+            Token::new_token(TokenType::IDENT, "y"),
+            Token::new_token(TokenType::RPAREN, ")"),
+            Token::new_token(TokenType::LBRACE, "{"),
+            Token::new_token(TokenType::IDENT, "x"),
+            Token::new_token(TokenType::PLUS, "+"),
+            Token::new_token(TokenType::IDENT, "y"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::RBRACE, "}"),
+            Token::new_token(TokenType::LET, "let"),
+            Token::new_token(TokenType::IDENT, "result"),
+            Token::new_token(TokenType::ASSIGN, "="),
+            Token::new_token(TokenType::IDENT, "add"),
+            Token::new_token(TokenType::LPAREN, "("),
+            Token::new_token(TokenType::IDENT, "five"),
+            Token::new_token(TokenType::COMMA, ","),
+            Token::new_token(TokenType::IDENT, "ten"),
+            Token::new_token(TokenType::RPAREN, ")"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::BANG, "!"),
+            Token::new_token(TokenType::MINUS, "-"),
+            Token::new_token(TokenType::SLASH, "/"),
+            Token::new_token(TokenType::ASTERISK, "*"),
+            Token::new_token(TokenType::INT(5), "5"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::INT(5), "5"),
+            Token::new_token(TokenType::LESSTHAN, "<"),
+            Token::new_token(TokenType::INT(10), "10"),
+            Token::new_token(TokenType::GREATERTHAN, ">"),
+            Token::new_token(TokenType::INT(5), "5"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::IF, "if"),
+            Token::new_token(TokenType::LPAREN, "("),
+            Token::new_token(TokenType::INT(5), "5"),
+            Token::new_token(TokenType::LESSTHAN, "<"),
+            Token::new_token(TokenType::INT(10), "10"),
+            Token::new_token(TokenType::RPAREN, ")"),
+            Token::new_token(TokenType::LBRACE, "{"),
+            Token::new_token(TokenType::RETURN, "return"),
+            Token::new_token(TokenType::TRUE, "true"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::RBRACE, "}"),
+            Token::new_token(TokenType::ELSE, "else"),
+            Token::new_token(TokenType::LBRACE, "{"),
+            Token::new_token(TokenType::RETURN, "return"),
+            Token::new_token(TokenType::FALSE, "false"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::RBRACE, "}"),
+            Token::new_token(TokenType::INT(10), "10"),
+            Token::new_token(TokenType::EQUAL, "=="),
+            Token::new_token(TokenType::INT(10), "10"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::INT(10), "10"),
+            Token::new_token(TokenType::NOTEQUAL, "!="),
+            Token::new_token(TokenType::INT(9), "9"),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::EOF, ""),
+        ];
+
+        for tt in tests.iter() {
+            let tok = lexer.next_token();
+            assert_eq!(tok.kind, tt.kind);
+            assert_eq!(tok.literal, tt.literal);
+        }
+    }
+
+    #[test]
+    fn test_next_token_first() {
+        let input = "=+(){},;";
+        // let input = "=+aa(){},;";
+        let mut lexer = Lexer::new(input);
+
+        let tests = [
+            Token::new_token(TokenType::ASSIGN, "="),
+            Token::new_token(TokenType::PLUS, "+"),
+            // Token::new_token(TokenType::ILLEGAL, "a"),
+            // Token::new_token(TokenType::ILLEGAL, "a"),
+            Token::new_token(TokenType::LPAREN, "("),
+            Token::new_token(TokenType::RPAREN, ")"),
+            Token::new_token(TokenType::LBRACE, "{"),
+            Token::new_token(TokenType::RBRACE, "}"),
+            Token::new_token(TokenType::COMMA, ","),
+            Token::new_token(TokenType::SEMICOLON, ";"),
+            Token::new_token(TokenType::EOF, ""),
+        ];
+
+        for tt in tests.iter() {
+            let tok = lexer.next_token();
+            assert_eq!(tok.kind, tt.kind);
+            assert_eq!(tok.literal, tt.literal);
+        }
+    }
+
+    #[test]
+    fn test_token_spans() {
+        let input = "let x = 10;\n  ünï == x";
+        let mut lexer = Lexer::new(input);
+
+        let expected = [
+            ("let", 0, 3, 1, 1),
+            ("x", 4, 5, 1, 5),
+            ("=", 6, 7, 1, 7),
+            ("10", 8, 10, 1, 9),
+            (";", 10, 11, 1, 11),
+            ("ünï", 14, 19, 2, 3),
+            ("==", 20, 22, 2, 7),
+            ("x", 23, 24, 2, 10),
+            ("", 24, 24, 2, 11),
+        ];
+
+        for (literal, start, end, line, column) in expected.iter() {
+            let tok = lexer.next_token();
+            assert_eq!(tok.literal, *literal);
+            assert_eq!(
+                tok.span,
+                Span {
+                    start: *start,
+                    end: *end,
+                    line: *line,
+                    column: *column,
+                },
+                "span of {:?}",
+                literal
+            );
+        }
+    }
+
+    #[test]
+    fn test_resume() {
+        let input = "let x = 10;\n  ünï == x";
+        let resumed: Vec<(String, usize, usize)> = Lexer::resume(input, 16)
+            .map(|tok| (tok.literal.to_string(), tok.span.start, tok.span.column))
+            .collect();
+
+        assert_eq!(
+            resumed,
+            [
+                ("nï".to_string(), 16, 4),
+                ("==".to_string(), 20, 7),
+                ("x".to_string(), 23, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_large_input() {
+        let line = "let value = add(value, 42) * 7;\n";
+        let input = line.repeat(20_000);
+        let tokens = tokenize(&input);
+
+        assert_eq!(tokens.len(), 20_000 * 12);
+        let last = tokens.last().unwrap();
+        assert_eq!(last.span.end, input.len() - 1);
+        assert_eq!(last.span.line, 20_000);
+    }
+
+    #[test]
+    fn test_iterator() {
+        let mut lexer = Lexer::new("let x = 5; x");
+
+        let kinds: Vec<TokenType> = lexer.by_ref().map(|tok| tok.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenType::LET,
+                TokenType::IDENT,
+                TokenType::ASSIGN,
+                TokenType::INT(5),
+                TokenType::SEMICOLON,
+                TokenType::IDENT,
+            ]
+        );
+
+        // Exhausted for good once EOF has been reached
+        assert_eq!(lexer.next(), None);
+        assert_eq!(lexer.next(), None);
+
+        let identifiers: Vec<String> = tokenize("let a = b + 1;")
+            .into_iter()
+            .filter(|tok| tok.kind == TokenType::IDENT)
+            .map(|tok| tok.literal.to_string())
+            .collect();
+        assert_eq!(identifiers, ["a", "b"]);
+        assert!(tokenize("  \n ").is_empty());
+    }
+
+    #[test]
+    fn test_tokens_borrow_from_input() {
+        let owned = {
+            let input = String::from("let answer = 42;");
+            let mut lexer = Lexer::new(&input);
+
+            lexer.next_token();
+            let tok = lexer.next_token();
+            assert_eq!(tok.kind, TokenType::IDENT);
+            assert!(matches!(tok.literal, Cow::Borrowed("answer")));
+            assert!(std::ptr::eq(tok.literal.as_ptr(), input[4..].as_ptr()));
+
+            tok.into_owned()
+        };
+
+        assert_eq!(owned.literal, "answer");
+        assert_eq!(owned.span.start, 4);
+    }
+
+    #[test]
+    fn test_strings() {
+        let tests = [
+            (r#""foobar""#, "foobar"),
+            (r#""foo bar""#, "foo bar"),
+            (r#""""#, ""),
+            (r#""a\nb\tc""#, "a\nb\tc"),
+            (r#""say \"hi\"""#, "say \"hi\""),
+            (r#""C:\\dir""#, "C:\\dir"),
+            (r#""\u{48}\u{1F600}\u{e9}""#, "H😀é"),
+            ("\"two\nlines\"", "two\nlines"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let tokens = tokenize(input);
+            assert_eq!(tokens.len(), 1, "input: {}", input);
+            assert_eq!(tokens[0].kind, TokenType::STRING(expected.to_string()));
+            assert_eq!(tokens[0].literal, *input);
+        }
+
+        let kinds: Vec<TokenType> = tokenize(r#"let s = "a" + "b";"#)
+            .into_iter()
+            .map(|tok| tok.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenType::LET,
+                TokenType::IDENT,
+                TokenType::ASSIGN,
+                TokenType::STRING("a".to_string()),
+                TokenType::PLUS,
+                TokenType::STRING("b".to_string()),
+                TokenType::SEMICOLON,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_string_errors() {
+        let span = |start, end, column| Span {
+            start,
+            end,
+            line: 1,
+            column,
+        };
+
+        let tests = [
+            (
+                r#"x = "abc"#,
+                LexError::UnterminatedString {
+                    span: span(4, 8, 5),
+                },
+            ),
+            (
+                r#"""#,
+                LexError::UnterminatedString {
+                    span: span(0, 1, 1),
+                },
+            ),
+            (
+                r#""a\qb""#,
+                LexError::InvalidEscape {
+                    escape: r"\q".to_string(),
+                    span: span(2, 4, 3),
+                },
+            ),
+            (
+                r#""\u{110000}""#,
+                LexError::InvalidUnicodeEscape {
+                    escape: r"\u{110000}".to_string(),
+                    span: span(1, 11, 2),
+                },
+            ),
+            (
+                r#""\u{}""#,
+                LexError::InvalidUnicodeEscape {
+                    escape: r"\u{}".to_string(),
+                    span: span(1, 5, 2),
+                },
+            ),
+            (
+                r#""\u41""#,
+                LexError::InvalidUnicodeEscape {
+                    escape: r"\u".to_string(),
+                    span: span(1, 3, 2),
+                },
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let error = tokenize(input)
+                .into_iter()
+                .find_map(|tok| match tok.kind {
+                    TokenType::ERROR(error) => Some(error),
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("no error for {}", input));
+            assert_eq!(error, expected, "input: {}", input);
+        }
+
+        // The whole string is skipped, lexing resumes after its closing quote
+        let kinds: Vec<TokenType> = tokenize(r#""\q" 1"#)
+            .into_iter()
+            .map(|tok| tok.kind)
+            .collect();
+        assert_eq!(kinds.len(), 2);
+        assert_eq!(kinds[1], TokenType::INT(1));
+    }
+
+    #[test]
+    fn test_comments_are_skipped() {
+        let input = "// leading comment
+let a = 1; // trailing comment
+/* block /* nested */ still comment */ a / 2
+/**/ /***/ a //";
+
+        let literals: Vec<String> = tokenize(input)
+            .into_iter()
+            .map(|tok| tok.literal.to_string())
+            .collect();
+        assert_eq!(literals, ["let", "a", "=", "1", ";", "a", "/", "2", "a"]);
+    }
+
+    #[test]
+    fn test_comments_as_tokens() {
+        let input = "a // line\n/* b /* c */ */ b";
+
+        let tokens: Vec<Token> = Lexer::new(input).with_comments().collect();
+        let kinds: Vec<&TokenType> = tokens.iter().map(|tok| &tok.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &TokenType::IDENT,
+                &TokenType::COMMENT,
+                &TokenType::COMMENT,
+                &TokenType::IDENT,
+            ]
+        );
+        assert_eq!(tokens[1].literal, "// line");
+        assert_eq!(tokens[2].literal, "/* b /* c */ */");
+        assert_eq!(tokens[2].span.line, 2);
+    }
+
+    #[test]
+    fn test_unterminated_comment() {
+        for input in ["1 /* open /* nested */", "1 /* open"] {
+            let tokens = tokenize(input);
+            assert_eq!(tokens.len(), 2, "input: {}", input);
+            assert_eq!(
+                tokens[1].kind,
+                TokenType::ERROR(LexError::UnterminatedComment {
+                    span: Span {
+                        start: 2,
+                        end: input.len(),
+                        line: 1,
+                        column: 3,
+                    },
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let input = "let x1 = _tmp_2 + größe + 变量 + café + ٣ + ½ + 🦀";
+
+        let tokens: Vec<(TokenType, String)> = tokenize(input)
+            .into_iter()
+            .map(|tok| (tok.kind, tok.literal.to_string()))
+            .collect();
+        let ident = |name: &str| (TokenType::IDENT, name.to_string());
+        let plus = || (TokenType::PLUS, "+".to_string());
+
+        assert_eq!(
+            tokens,
+            [
+                (TokenType::LET, "let".to_string()),
+                ident("x1"),
+                (TokenType::ASSIGN, "=".to_string()),
+                ident("_tmp_2"),
+                plus(),
+                ident("größe"),
+                plus(),
+                ident("变量"),
+                plus(),
+                ident("café"),
+                plus(),
+                (TokenType::ILLEGAL, "٣".to_string()),
+                plus(),
+                (TokenType::ILLEGAL, "½".to_string()),
+                plus(),
+                (TokenType::ILLEGAL, "🦀".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numbers_are_ascii_digits() {
+        let kinds: Vec<TokenType> = tokenize("12٣4 1x")
+            .into_iter()
+            .map(|tok| tok.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenType::INT(12),
+                TokenType::ILLEGAL,
+                TokenType::INT(4),
+                TokenType::INT(1),
+                TokenType::IDENT,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_integer_literals() {
+        let tests = [
+            ("0", 0),
+            ("0755", 755),
+            ("1_000_000", 1_000_000),
+            ("0x1F", 0x1f),
+            ("0xdead_BEEF", 0xdead_beef),
+            ("0o755", 0o755),
+            ("0b1010_0101", 0b1010_0101),
+            ("0x_ff", 0xff),
+            ("9223372036854775807", i64::MAX),
+            ("0x7fffffffffffffff", i64::MAX),
+        ];
+
+        for (input, expected) in tests {
+            let tokens = tokenize(input);
+            assert_eq!(tokens.len(), 1, "input: {}", input);
+            assert_eq!(tokens[0].kind, TokenType::INT(expected), "input: {}", input);
+            assert_eq!(tokens[0].literal, input);
+        }
+
+        let kinds: Vec<TokenType> = tokenize("0x10+0b1;")
+            .into_iter()
+            .map(|tok| tok.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenType::INT(16),
+                TokenType::PLUS,
+                TokenType::INT(1),
+                TokenType::SEMICOLON,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_integer_errors() {
+        let span = |start, end| Span {
+            start,
+            end,
+            line: 1,
+            column: start + 1,
+        };
+
+        let tests = [
+            (
+                "99999999999999999999999",
+                LexError::IntegerOutOfRange {
+                    literal: "99999999999999999999999".to_string(),
+                    span: span(0, 23),
+                },
+            ),
+            (
+                "x = 9_223_372_036_854_775_808;",
+                LexError::IntegerOutOfRange {
+                    literal: "9_223_372_036_854_775_808".to_string(),
+                    span: span(4, 29),
+                },
+            ),
+            (
+                "0x1_0000_0000_0000_0000",
+                LexError::IntegerOutOfRange {
+                    literal: "0x1_0000_0000_0000_0000".to_string(),
+                    span: span(0, 23),
+                },
+            ),
+            (
+                "0b102 + 1",
+                LexError::InvalidInteger {
+                    literal: "0b102".to_string(),
+                    span: span(0, 5),
+                },
+            ),
+            (
+                "0xfg",
+                LexError::InvalidInteger {
+                    literal: "0xfg".to_string(),
+                    span: span(0, 4),
+                },
+            ),
+            (
+                "0x;",
+                LexError::InvalidInteger {
+                    literal: "0x".to_string(),
+                    span: span(0, 2),
+                },
+            ),
+            (
+                "0o_",
+                LexError::InvalidInteger {
+                    literal: "0o_".to_string(),
+                    span: span(0, 3),
+                },
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let tokens = tokenize(input);
+            let error = tokens
+                .iter()
+                .find_map(|tok| match &tok.kind {
+                    TokenType::ERROR(error) => Some(error.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("no error for {}", input));
+            assert_eq!(error, expected, "input: {}", input);
+        }
+
+        // Lexing carries on after the bad literal
+        let kinds: Vec<TokenType> = tokenize("0x; 1").into_iter().map(|tok| tok.kind).collect();
+        assert_eq!(kinds[1..], [TokenType::SEMICOLON, TokenType::INT(1)]);
+    }
+
+    #[test]
+    fn test_float_literals() {
+        let tests = [
+            ("2.75", 2.75),
+            ("0.5", 0.5),
+            ("1_000.000_1", 1000.0001),
+            ("1e-9", 1e-9),
+            ("2.5E+3", 2500.0),
+            ("6e23", 6e23),
+            ("1.7976931348623157e308", f64::MAX),
+        ];
+
+        for (input, expected) in tests {
+            let tokens = tokenize(input);
+            assert_eq!(tokens.len(), 1, "input: {}", input);
+            assert_eq!(
+                tokens[0].kind,
+                TokenType::FLOAT(expected),
+                "input: {}",
+                input
+            );
+            assert_eq!(tokens[0].literal, input);
+        }
+
+        // Digits are needed after the point and after an `e`
+        let kinds: Vec<TokenType> = tokenize("5. 1e x.5")
+            .into_iter()
+            .map(|tok| tok.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenType::INT(5),
+                TokenType::ILLEGAL,
+                TokenType::INT(1),
+                TokenType::IDENT,
+                TokenType::IDENT,
+                TokenType::ERROR(LexError::MissingIntegerPart {
+                    literal: ".5".to_string(),
+                    span: Span {
+                        start: 7,
+                        end: 9,
+                        line: 1,
+                        column: 8,
+                    },
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_float_errors() {
+        let tests = [
+            ("1e400", "float literal `1e400` is out of range"),
+            (
+                ".5e3",
+                "float literal `.5e3` needs a digit before the point",
+            ),
+            (
+                "x = .25;",
+                "float literal `.25` needs a digit before the point",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let error = tokenize(input)
+                .into_iter()
+                .find_map(|tok| match tok.kind {
+                    TokenType::ERROR(error) => Some(error),
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("no error for {}", input));
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_operators() {
+        let input = "<= >= < > % && || += -= *= /= & | a/=b [ ] :";
+
+        let tokens: Vec<(TokenType, String)> = tokenize(input)
+            .into_iter()
+            .map(|tok| (tok.kind, tok.literal.to_string()))
+            .collect();
+        let expected = [
+            (TokenType::LESSEQUAL, "<="),
+            (TokenType::GREATEREQUAL, ">="),
+            (TokenType::LESSTHAN, "<"),
+            (TokenType::GREATERTHAN, ">"),
+            (TokenType::PERCENT, "%"),
+            (TokenType::AND, "&&"),
+            (TokenType::OR, "||"),
+            (TokenType::PLUSASSIGN, "+="),
+            (TokenType::MINUSASSIGN, "-="),
+            (TokenType::ASTERISKASSIGN, "*="),
+            (TokenType::SLASHASSIGN, "/="),
+            (TokenType::ILLEGAL, "&"),
+            (TokenType::ILLEGAL, "|"),
+            (TokenType::IDENT, "a"),
+            (TokenType::SLASHASSIGN, "/="),
+            (TokenType::IDENT, "b"),
+            (TokenType::LBRACKET, "["),
+            (TokenType::RBRACKET, "]"),
+            (TokenType::COLON, ":"),
+        ];
+
+        let expected: Vec<(TokenType, String)> = expected
+            .into_iter()
+            .map(|(kind, literal)| (kind, literal.to_string()))
+            .collect();
+        assert_eq!(tokens, expected);
+    }
+}
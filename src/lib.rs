@@ -0,0 +1,21 @@
+//! Junkie, a small interpreted language. Embed it in a Rust program through
+//! [`Interpreter`], or build tools on its front end: [`Lexer`], [`Parser`],
+//! the syntax tree in [`ast`] and the [`parse`] shortcut. [`diagnostics`]
+//! renders parse and runtime errors against the source they came from
+#![allow(clippy::needless_return)]
+
+mod eval;
+mod interpreter;
+mod utils;
+
+pub mod ast;
+pub mod diagnostics;
+pub mod lexer;
+pub mod object;
+pub mod parser;
+pub mod token;
+
+pub use interpreter::{Error, Interpreter};
+pub use lexer::{LexError, Lexer};
+pub use object::{FromObject, IntoObject, Object};
+pub use parser::{parse, ParseError, Parser};
@@ -1,13 +1,12 @@
-mod lexer;
-mod repl;
-mod utils;
+#![allow(clippy::needless_return)]
 
-pub mod ast;
-pub mod parser;
-pub mod token;
+mod cli;
+mod repl;
 
-use repl::start;
+use std::env;
+use std::process::ExitCode;
 
-fn main() {
-    start();
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    return cli::run(&args);
 }
@@ -0,0 +1,238 @@
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use super::{HashKey, Object};
+
+/// Converts a Rust value into the Junkie value it stands for
+pub trait IntoObject {
+    fn into_object(self) -> Object;
+}
+
+/// Converts a Junkie value into a Rust value. Fails with a message naming
+/// the expected and actual types when they do not match
+pub trait FromObject: Sized {
+    fn from_object(object: &Object) -> Result<Self, String>;
+}
+
+fn mismatch(expected: &str, found: &Object) -> String {
+    return format!("expected {}, got {}", expected, found.type_name());
+}
+
+impl IntoObject for Object {
+    fn into_object(self) -> Object {
+        return self;
+    }
+}
+
+impl IntoObject for i64 {
+    fn into_object(self) -> Object {
+        return Object::Integer(self);
+    }
+}
+
+impl IntoObject for i32 {
+    fn into_object(self) -> Object {
+        return Object::Integer(self.into());
+    }
+}
+
+impl IntoObject for f64 {
+    fn into_object(self) -> Object {
+        return Object::Float(self);
+    }
+}
+
+impl IntoObject for bool {
+    fn into_object(self) -> Object {
+        return Object::Boolean(self);
+    }
+}
+
+impl IntoObject for String {
+    fn into_object(self) -> Object {
+        return Object::String(self);
+    }
+}
+
+impl IntoObject for &str {
+    fn into_object(self) -> Object {
+        return Object::String(self.to_string());
+    }
+}
+
+/// Functions called only for their effect return `()`, which becomes null
+impl IntoObject for () {
+    fn into_object(self) -> Object {
+        return Object::Null;
+    }
+}
+
+impl<T: IntoObject> IntoObject for Option<T> {
+    fn into_object(self) -> Object {
+        match self {
+            Some(value) => value.into_object(),
+            None => Object::Null,
+        }
+    }
+}
+
+impl<T: IntoObject> IntoObject for Vec<T> {
+    fn into_object(self) -> Object {
+        let elements = self.into_iter().map(IntoObject::into_object).collect();
+        return Object::Array(Rc::new(elements));
+    }
+}
+
+impl<T: IntoObject> IntoObject for HashMap<String, T> {
+    fn into_object(self) -> Object {
+        let pairs: BTreeMap<HashKey, Object> = self
+            .into_iter()
+            .map(|(key, value)| (HashKey::String(key), value.into_object()))
+            .collect();
+        return Object::Hash(Rc::new(pairs));
+    }
+}
+
+impl FromObject for Object {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        return Ok(object.clone());
+    }
+}
+
+impl FromObject for i64 {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        match object {
+            Object::Integer(value) => Ok(*value),
+            other => Err(mismatch("INTEGER", other)),
+        }
+    }
+}
+
+/// Integers are accepted too, the same way arithmetic promotes them
+impl FromObject for f64 {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        match object {
+            Object::Float(value) => Ok(*value),
+            Object::Integer(value) => Ok(*value as f64),
+            other => Err(mismatch("FLOAT", other)),
+        }
+    }
+}
+
+impl FromObject for bool {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        match object {
+            Object::Boolean(value) => Ok(*value),
+            other => Err(mismatch("BOOLEAN", other)),
+        }
+    }
+}
+
+impl FromObject for String {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        match object {
+            Object::String(value) => Ok(value.clone()),
+            other => Err(mismatch("STRING", other)),
+        }
+    }
+}
+
+/// `null` becomes `None`, anything else must convert to `T`
+impl<T: FromObject> FromObject for Option<T> {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        match object {
+            Object::Null => Ok(None),
+            other => T::from_object(other).map(Some),
+        }
+    }
+}
+
+impl<T: FromObject> FromObject for Vec<T> {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        match object {
+            Object::Array(elements) => elements.iter().map(T::from_object).collect(),
+            other => Err(mismatch("ARRAY", other)),
+        }
+    }
+}
+
+/// Only hashes whose keys are all strings convert
+impl<T: FromObject> FromObject for HashMap<String, T> {
+    fn from_object(object: &Object) -> Result<Self, String> {
+        let pairs = match object {
+            Object::Hash(pairs) => pairs,
+            other => return Err(mismatch("HASH", other)),
+        };
+
+        let mut map = HashMap::with_capacity(pairs.len());
+        for (key, value) in pairs.iter() {
+            match key {
+                HashKey::String(key) => map.insert(key.clone(), T::from_object(value)?),
+                key => return Err(format!("expected STRING keys, got {}", key)),
+            };
+        }
+        return Ok(map);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_into_object() {
+        let mut map = HashMap::new();
+        map.insert("b".to_string(), vec![Some(1.5), None]);
+        map.insert("a".to_string(), vec![]);
+
+        let tests = [
+            (42i64.into_object(), "42"),
+            (7i32.into_object(), "7"),
+            (0.5.into_object(), "0.5"),
+            (true.into_object(), "true"),
+            ("hi".into_object(), "hi"),
+            (().into_object(), "null"),
+            (vec!["a", "b"].into_object(), r#"["a", "b"]"#),
+            (map.into_object(), r#"{"a": [], "b": [1.5, null]}"#),
+        ];
+
+        for (object, expected) in tests.iter() {
+            assert_eq!(object.to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_from_object() {
+        assert_eq!(i64::from_object(&Object::Integer(3)), Ok(3));
+        assert_eq!(f64::from_object(&Object::Integer(3)), Ok(3.0));
+        assert_eq!(String::from_object(&"x".into_object()), Ok("x".to_string()));
+        assert_eq!(
+            Vec::<Option<bool>>::from_object(&vec![Some(true), None].into_object()),
+            Ok(vec![Some(true), None])
+        );
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), 1i64);
+        assert_eq!(
+            HashMap::<String, i64>::from_object(&map.clone().into_object()),
+            Ok(map)
+        );
+
+        assert_eq!(
+            i64::from_object(&Object::Float(1.0)),
+            Err("expected INTEGER, got FLOAT".to_string())
+        );
+        assert_eq!(
+            Vec::<i64>::from_object(&vec![1.into_object(), true.into_object()].into_object()),
+            Err("expected INTEGER, got BOOLEAN".to_string())
+        );
+        assert_eq!(
+            HashMap::<String, i64>::from_object(&Object::Hash(Rc::new(BTreeMap::from([(
+                HashKey::Integer(1),
+                Object::Integer(1)
+            )])))),
+            Err("expected STRING keys, got 1".to_string())
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use super::Object;
+
+/// Shared handle to an environment. Function objects and enclosed scopes hold on to these
+pub type Env = Rc<RefCell<Environment>>;
+
+/// The environment a function was defined in
+///
+/// A function bound in the very environment it captured (e.g. `let f = fn() { f() };`)
+/// would otherwise keep that environment alive forever through an Rc cycle, so such
+/// bindings only hold a weak reference. `Environment::get` hands out strong copies again.
+/// Other cycles, such as a closure stored in a scope enclosing the one it captured or a
+/// function inside an array, last until `Environment::clear` is called on the top level.
+#[derive(Debug, Clone)]
+pub enum CapturedEnv {
+    Strong(Env),
+    Weak(Weak<RefCell<Environment>>),
+}
+
+impl CapturedEnv {
+    /// Returns the captured environment, or None if it has already been dropped
+    pub fn upgrade(&self) -> Option<Env> {
+        match self {
+            CapturedEnv::Strong(env) => Some(Rc::clone(env)),
+            CapturedEnv::Weak(env) => env.upgrade(),
+        }
+    }
+}
+
+/// Every scope enclosed in one top level environment, so they can all be cleared at once.
+/// Entries of scopes that were already dropped are pruned whenever the list is full
+#[derive(Debug, Default)]
+struct Scopes(RefCell<Vec<Weak<RefCell<Environment>>>>);
+
+impl Scopes {
+    fn track(&self, env: &Env) {
+        let mut scopes = self.0.borrow_mut();
+        if scopes.len() == scopes.capacity() {
+            scopes.retain(|scope| scope.strong_count() > 0);
+        }
+        scopes.push(Rc::downgrade(env));
+    }
+}
+
+/// Name to value bindings of a single scope, with a pointer to the enclosing one
+#[derive(Debug, Default)]
+pub struct Environment {
+    store: HashMap<String, Object>,
+    outer: Option<Env>,
+    scopes: Rc<Scopes>,
+}
+
+impl Environment {
+    /// Returns a new top level environment
+    pub fn new() -> Environment {
+        return Environment::default();
+    }
+
+    /// Returns a new, empty scope nested inside `outer`.
+    /// Nothing is allocated until the first binding is made
+    pub fn new_enclosed(outer: &Env) -> Env {
+        let scopes = Rc::clone(&outer.borrow().scopes);
+        let env = Rc::new(RefCell::new(Environment {
+            store: HashMap::new(),
+            outer: Some(Rc::clone(outer)),
+            scopes: Rc::clone(&scopes),
+        }));
+        scopes.track(&env);
+        return env;
+    }
+
+    /// Removes every binding from this environment and from all scopes ever created under
+    /// its top level environment, freeing any cycles between functions and the scopes they
+    /// captured. Functions that are still held elsewhere fail to find any name once called
+    pub fn clear(&mut self) {
+        self.store.clear();
+        let scopes = std::mem::take(&mut *self.scopes.0.borrow_mut());
+        for scope in scopes.iter().filter_map(Weak::upgrade) {
+            if let Ok(mut scope) = scope.try_borrow_mut() {
+                scope.store.clear();
+                scope.outer = None;
+            }
+        }
+    }
+
+    /// Looks `name` up in this scope, then in the enclosing ones
+    pub fn get(&self, name: &str) -> Option<Object> {
+        match self.store.get(name) {
+            Some(Object::Function(function)) => {
+                let mut function = function.clone();
+                if let CapturedEnv::Weak(env) = &function.env {
+                    function.env = CapturedEnv::Strong(env.upgrade()?);
+                }
+                return Some(Object::Function(function));
+            }
+            Some(value) => return Some(value.clone()),
+            None => {}
+        }
+
+        match &self.outer {
+            Some(outer) => return outer.borrow().get(name),
+            None => return None,
+        }
+    }
+
+    /// Binds `name` to `value` in this scope, shadowing any outer binding
+    pub fn set(&mut self, name: String, value: Object) {
+        let value = match value {
+            Object::Function(mut function) => {
+                if let CapturedEnv::Strong(env) = &function.env {
+                    if std::ptr::eq(env.as_ptr(), self) {
+                        function.env = CapturedEnv::Weak(Rc::downgrade(env));
+                    }
+                }
+                Object::Function(function)
+            }
+            value => value,
+        };
+
+        self.store.insert(name, value);
+    }
+
+    /// Rebinds `name` in the innermost scope that binds it.
+    /// Returns false, binding nothing, if no scope does
+    pub fn assign(&mut self, name: &str, value: Object) -> bool {
+        if self.store.contains_key(name) {
+            self.set(name.to_string(), value);
+            return true;
+        }
+
+        match &self.outer {
+            Some(outer) => return outer.borrow_mut().assign(name, value),
+            None => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_get_and_set() {
+        let mut env = Environment::new();
+        env.set("x".to_string(), Object::Integer(5));
+
+        assert!(matches!(env.get("x"), Some(Object::Integer(5))));
+        assert!(env.get("y").is_none());
+    }
+
+    #[test]
+    fn test_enclosed_scopes() {
+        let outer = Rc::new(RefCell::new(Environment::new()));
+        outer.borrow_mut().set("x".to_string(), Object::Integer(1));
+        outer.borrow_mut().set("y".to_string(), Object::Integer(2));
+
+        let inner = Environment::new_enclosed(&outer);
+        inner.borrow_mut().set("x".to_string(), Object::Integer(10));
+
+        assert!(matches!(inner.borrow().get("x"), Some(Object::Integer(10))));
+        assert!(matches!(inner.borrow().get("y"), Some(Object::Integer(2))));
+        assert!(matches!(outer.borrow().get("x"), Some(Object::Integer(1))));
+        assert_eq!(Rc::strong_count(&outer), 2);
+    }
+
+    #[test]
+    fn test_assign() {
+        let outer = Rc::new(RefCell::new(Environment::new()));
+        outer.borrow_mut().set("x".to_string(), Object::Integer(1));
+
+        let inner = Environment::new_enclosed(&outer);
+        assert!(inner.borrow_mut().assign("x", Object::Integer(2)));
+        assert!(!inner.borrow_mut().assign("y", Object::Integer(3)));
+
+        assert!(matches!(outer.borrow().get("x"), Some(Object::Integer(2))));
+        assert!(inner.borrow().get("y").is_none());
+    }
+
+    #[test]
+    fn test_clear() {
+        let outer = Rc::new(RefCell::new(Environment::new()));
+        let inner = Environment::new_enclosed(&outer);
+        let innermost = Environment::new_enclosed(&inner);
+        innermost
+            .borrow_mut()
+            .set("x".to_string(), Object::Integer(1));
+        drop(inner);
+
+        outer.borrow_mut().clear();
+
+        assert!(innermost.borrow().get("x").is_none());
+        assert!(innermost.borrow().outer.is_none());
+        assert_eq!(Rc::strong_count(&outer), 1);
+    }
+}
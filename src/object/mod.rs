@@ -0,0 +1,207 @@
+mod convert;
+mod environment;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast;
+use crate::token::Span;
+
+pub use convert::{FromObject, IntoObject};
+pub use environment::{CapturedEnv, Env, Environment};
+
+/// Runtime values produced by the evaluator
+#[derive(Debug, Clone)]
+pub enum Object {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    /// Shared, since arrays are never modified in place
+    Array(Rc<Vec<Object>>),
+    /// Kept sorted by key, so a hash prints the same way on every run
+    Hash(Rc<BTreeMap<HashKey, Object>>),
+    Null,
+    /// Wraps the value of a `return` statement while it bubbles up through nested blocks
+    ReturnValue(Box<Object>),
+    Error(RuntimeError),
+    Function(Function),
+    Builtin(Builtin),
+}
+
+impl Object {
+    /// Name of the value's type, as used in runtime error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "INTEGER",
+            Object::Float(_) => "FLOAT",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+            Object::Null => "NULL",
+            Object::ReturnValue(_) => "RETURN_VALUE",
+            Object::Error(_) => "ERROR",
+            Object::Function(_) => "FUNCTION",
+            Object::Builtin(_) => "BUILTIN",
+        }
+    }
+
+    /// The key this value stands for in a hash, or None if it cannot be one
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(value) => Some(HashKey::Integer(*value)),
+            Object::Boolean(value) => Some(HashKey::Boolean(*value)),
+            Object::String(value) => Some(HashKey::String(value.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        return matches!(self, Object::Error(_));
+    }
+
+    /// Errors and `return` values cut evaluation short, so wherever a value is
+    /// used as an operand, argument or binding they must be passed up instead
+    pub fn is_abrupt(&self) -> bool {
+        return matches!(self, Object::Error(_) | Object::ReturnValue(_));
+    }
+
+    /// `null` and `false` are falsy, everything else is truthy
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Object::Null => false,
+            Object::Boolean(value) => *value,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            Object::Float(value) => write!(f, "{:?}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Array(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_element(f, element)?;
+                }
+                write!(f, "]")
+            }
+            Object::Hash(pairs) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", key)?;
+                    write_element(f, value)?;
+                }
+                write!(f, "}}")
+            }
+            Object::Null => write!(f, "null"),
+            Object::ReturnValue(value) => write!(f, "{}", value),
+            Object::Error(error) => write!(f, "ERROR: {}", error.message),
+            Object::Function(function) => write!(f, "{}", function),
+            Object::Builtin(builtin) => write!(f, "builtin function {}", builtin.name),
+        }
+    }
+}
+
+/// Writes a value nested in an array or hash. Strings are quoted, so that
+/// `["a, b"]` and `["a", "b"]` print differently
+fn write_element(f: &mut fmt::Formatter, value: &Object) -> fmt::Result {
+    match value {
+        Object::String(value) => write!(f, "{:?}", value),
+        value => write!(f, "{}", value),
+    }
+}
+
+/// The hashable subset of values. Two values are the same key exactly when
+/// they have the same type and are equal
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HashKey {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+impl fmt::Display for HashKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashKey::Integer(value) => write!(f, "{}", value),
+            HashKey::Boolean(value) => write!(f, "{}", value),
+            HashKey::String(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+/// An error raised while evaluating, pointing at the expression that caused it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for RuntimeError {}
+
+/// A user defined function, created by evaluating an `ast::FunctionLiteral`.
+/// The AST parts are shared so that copying the value around stays cheap
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub parameters: Rc<Vec<ast::Identifier>>,
+    pub body: Rc<ast::BlockStatement>,
+    pub env: CapturedEnv,
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parameters: Vec<&str> = self.parameters.iter().map(|p| p.name.as_str()).collect();
+        write!(f, "fn({}) {}", parameters.join(", "), self.body)
+    }
+}
+
+/// Signature of a native function. An `Err` becomes a runtime error at the call site
+pub type BuiltinFn = Rc<dyn Fn(&[Object]) -> Result<Object, String>>;
+
+/// A function implemented in Rust, either one of the builtins such as `len`
+/// or one registered by the host through `Interpreter::register_fn`
+#[derive(Clone)]
+pub struct Builtin {
+    pub name: Rc<str>,
+    pub function: BuiltinFn,
+}
+
+impl Builtin {
+    pub fn new<F>(name: &str, function: F) -> Builtin
+    where
+        F: Fn(&[Object]) -> Result<Object, String> + 'static,
+    {
+        return Builtin {
+            name: Rc::from(name),
+            function: Rc::new(function),
+        };
+    }
+}
+
+impl fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builtin")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
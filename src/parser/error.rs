@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::lexer::LexError;
+use crate::token::{Span, TokenType};
+
+use super::MAX_NESTING;
+
+/// Everything that can go wrong while turning tokens into an AST
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The parser required a specific token next and got something else
+    UnexpectedToken {
+        expected: TokenType,
+        found: TokenType,
+        span: Span,
+    },
+    /// A `{` block reached the end of input before its closing `}`
+    UnterminatedBlock { span: Span },
+    /// The lexer could not make sense of a character
+    IllegalToken { literal: String, span: Span },
+    /// An expression was expected but the token cannot start one
+    MissingPrefixParseFn { found: TokenType, span: Span },
+    /// Something other than a name on the left of `=` or `+=`
+    InvalidAssignmentTarget { span: Span },
+    /// Expressions nested deeper than `MAX_NESTING`
+    NestedTooDeeply { span: Span },
+    /// The lexer rejected part of the source
+    Lexical { error: LexError },
+}
+
+impl ParseError {
+    /// Location in the source the error points at
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::UnexpectedToken { span, .. } => *span,
+            ParseError::UnterminatedBlock { span } => *span,
+            ParseError::IllegalToken { span, .. } => *span,
+            ParseError::MissingPrefixParseFn { span, .. } => *span,
+            ParseError::InvalidAssignmentTarget { span } => *span,
+            ParseError::NestedTooDeeply { span } => *span,
+            ParseError::Lexical { error } => error.span(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedToken {
+                expected, found, ..
+            } => write!(f, "expected {}, found {}", expected, found),
+            ParseError::UnterminatedBlock { .. } => {
+                write!(f, "unterminated block, expected `}}` before end of input")
+            }
+            ParseError::IllegalToken { literal, .. } => {
+                write!(f, "illegal character `{}`", literal)
+            }
+            ParseError::MissingPrefixParseFn { found, .. } => {
+                write!(f, "expected an expression, found {}", found)
+            }
+            ParseError::InvalidAssignmentTarget { .. } => {
+                write!(f, "invalid assignment target, expected an identifier")
+            }
+            ParseError::NestedTooDeeply { .. } => write!(
+                f,
+                "expression nested too deeply, the limit is {} levels",
+                MAX_NESTING
+            ),
+            ParseError::Lexical { error } => write!(f, "{}", error),
+        }
+    }
+}
+
+// A lexical error is displayed as is, so it is not also reported as the source
+impl Error for ParseError {}
@@ -1,48 +1,164 @@
 #![allow(dead_code)]
 
+mod error;
+mod precedence;
+
 use crate::ast;
 use crate::ast::Identifier;
-use crate::lexer::Lexer;
+use crate::lexer::{is_min_magnitude, LexError, Lexer};
 use crate::token;
 use crate::token::TokenType;
 
+pub use error::ParseError;
+use precedence::Precedence;
+
+/// How deep expressions may nest. Real code stays far below this, and the
+/// bound keeps every later walk over the tree, such as printing it, shallow
+pub const MAX_NESTING: usize = 256;
+
+/// Stack left before nested parsing moves onto a fresh segment, and that segment's size
+const RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT: usize = 2 * 1024 * 1024;
+
+type PrefixParseFn<'src> = fn(&mut Parser<'src>) -> Option<ast::Expression>;
+type InfixParseFn<'src> = fn(&mut Parser<'src>, ast::Expression) -> Option<ast::Expression>;
+
+/// Looks up the function that parses a token in prefix position
+fn prefix_parse_fn<'src>(kind: &TokenType) -> Option<PrefixParseFn<'src>> {
+    match kind {
+        TokenType::IDENT => Some(Parser::parse_identifier),
+        TokenType::INT(_) => Some(Parser::parse_integer_literal),
+        TokenType::FLOAT(_) => Some(Parser::parse_float_literal),
+        TokenType::STRING(_) => Some(Parser::parse_string_literal),
+        TokenType::TRUE | TokenType::FALSE => Some(Parser::parse_boolean),
+        TokenType::BANG | TokenType::MINUS => Some(Parser::parse_prefix_expression),
+        TokenType::LPAREN => Some(Parser::parse_grouped_expression),
+        TokenType::IF => Some(Parser::parse_if_expression),
+        TokenType::FUNCTION => Some(Parser::parse_function_literal),
+        TokenType::LBRACKET => Some(Parser::parse_array_literal),
+        TokenType::LBRACE => Some(Parser::parse_hash_literal),
+        _ => None,
+    }
+}
+
+/// Looks up the function that parses a token in infix position
+fn infix_parse_fn<'src>(kind: &TokenType) -> Option<InfixParseFn<'src>> {
+    match kind {
+        TokenType::PLUS
+        | TokenType::MINUS
+        | TokenType::ASTERISK
+        | TokenType::SLASH
+        | TokenType::PERCENT
+        | TokenType::LESSTHAN
+        | TokenType::GREATERTHAN
+        | TokenType::LESSEQUAL
+        | TokenType::GREATEREQUAL
+        | TokenType::EQUAL
+        | TokenType::NOTEQUAL
+        | TokenType::AND
+        | TokenType::OR => Some(Parser::parse_infix_expression),
+        TokenType::ASSIGN
+        | TokenType::PLUSASSIGN
+        | TokenType::MINUSASSIGN
+        | TokenType::ASTERISKASSIGN
+        | TokenType::SLASHASSIGN => Some(Parser::parse_assign_expression),
+        TokenType::LPAREN => Some(Parser::parse_call_expression),
+        TokenType::LBRACKET => Some(Parser::parse_index_expression),
+        _ => None,
+    }
+}
+
+/// Parses `source` into a program, or returns every error found in it
+///
+/// ```
+/// let program = junkie::parse("let x = 1 + 2 * 3;").unwrap();
+/// assert_eq!(program.to_string(), "let x = (1 + (2 * 3));\n");
+///
+/// let errors = junkie::parse("let = 1;").unwrap_err();
+/// assert_eq!(errors[0].to_string(), "expected identifier, found `=`");
+/// ```
+pub fn parse(source: &str) -> Result<ast::Program, Vec<ParseError>> {
+    let mut parser = Parser::new(Lexer::new(source));
+    return parser.parse_program();
+}
+
+/// Pratt parser over the tokens of a `Lexer`. Use `parse` unless the
+/// lexer needs configuring, or `parse_program_partial` to keep what parsed
 #[derive(Debug)]
-struct Parser {
-    lexer: Lexer,
+pub struct Parser<'src> {
+    lexer: Lexer<'src>,
 
-    current_token: token::Token,
-    peek_token: token::Token,
-    errors: Vec<String>,
+    current_token: token::Token<'src>,
+    peek_token: token::Token<'src>,
+    errors: Vec<ParseError>,
+    /// Expressions currently being parsed inside one another
+    depth: usize,
 }
 
-impl Parser {
-    pub fn new(mut lexer: Lexer) -> Parser {
-        let current_token = lexer.next_token();
-        let peek_token = lexer.next_token();
+impl<'src> Parser<'src> {
+    pub fn new(mut lexer: Lexer<'src>) -> Parser<'src> {
+        let current_token = Parser::lex(&mut lexer);
+        let peek_token = Parser::lex(&mut lexer);
 
         return Parser {
             lexer,
             current_token,
             peek_token,
             errors: Vec::new(),
+            depth: 0,
         };
     }
 
     fn next_token(&mut self) {
-        self.current_token = std::mem::replace(&mut self.peek_token, self.lexer.next_token());
+        let next = Parser::lex(&mut self.lexer);
+        self.current_token = std::mem::replace(&mut self.peek_token, next);
+    }
+
+    /// Next token from the lexer, passing over comments should it emit them
+    fn lex(lexer: &mut Lexer<'src>) -> token::Token<'src> {
+        loop {
+            let token = lexer.next_token();
+            if token.kind != TokenType::COMMENT {
+                return token;
+            }
+        }
     }
 
-    pub fn errors(&self) -> Vec<String> {
-        // TODO: do this without cloning
-        return self.errors.clone();
+    /// Errors collected so far. `parse_program` hands them over when it fails
+    pub fn errors(&self) -> &[ParseError] {
+        return &self.errors;
     }
 
     fn peek_error(&mut self, kind: TokenType) {
-        let msg = format!(
-            "Expected next token to be {:?}, got {:?} instead",
-            kind, self.peek_token.kind
-        );
-        self.errors.push(msg);
+        // What the lexer rejected is more useful to report than the mismatch
+        let error = match &self.peek_token.kind {
+            TokenType::ERROR(error) => ParseError::Lexical {
+                error: error.clone(),
+            },
+            found => ParseError::UnexpectedToken {
+                expected: kind,
+                found: found.clone(),
+                span: self.peek_token.span,
+            },
+        };
+        self.errors.push(error);
+    }
+
+    fn no_prefix_parse_fn_error(&mut self) {
+        let error = match &self.current_token.kind {
+            TokenType::ERROR(error) => ParseError::Lexical {
+                error: error.clone(),
+            },
+            TokenType::ILLEGAL => ParseError::IllegalToken {
+                literal: self.current_token.literal.to_string(),
+                span: self.current_token.span,
+            },
+            _ => ParseError::MissingPrefixParseFn {
+                found: self.current_token.kind.clone(),
+                span: self.current_token.span,
+            },
+        };
+        self.errors.push(error);
     }
 
     fn expect_peek(&mut self, kind: TokenType) -> bool {
@@ -55,33 +171,92 @@ impl Parser {
         }
     }
 
-    fn parse_program(&mut self) -> Option<ast::Program> {
+    fn peek_precedence(&self) -> Precedence {
+        return Precedence::of(&self.peek_token.kind);
+    }
+
+    fn current_precedence(&self) -> Precedence {
+        return Precedence::of(&self.current_token.kind);
+    }
+
+    /// Builds an Identifier node out of the current token
+    fn current_identifier(&self) -> Identifier {
+        return Identifier {
+            token: self.current_token.clone().into_owned(),
+            span: self.current_token.span,
+            name: self.current_token.literal.to_string(),
+        };
+    }
+
+    /// Parses the whole input. Returns every error encountered if any statement failed to parse
+    pub fn parse_program(&mut self) -> Result<ast::Program, Vec<ParseError>> {
+        let (program, errors) = self.parse_program_partial();
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        return Ok(program);
+    }
+
+    /// Parses the whole input, recovering from errors at statement boundaries.
+    /// Returns the statements that did parse along with every error encountered
+    pub fn parse_program_partial(&mut self) -> (ast::Program, Vec<ParseError>) {
         // create root node
         let mut program = ast::Program {
             statements: Vec::new(),
         };
         // populate root node with statements
         while self.current_token.kind != token::TokenType::EOF {
-            if let Some(stm) = self.parse_statement() {
-                program.statements.push(stm);
+            let start = self.current_token.span;
+            match self.parse_statement() {
+                Some(stm) => {
+                    program.statements.push(stm);
+                    // jumps over the semicolon
+                    self.next_token();
+                }
+                None => {
+                    self.synchronize(start);
+                    // a `}` only stops recovery inside blocks, at the top level it is stray
+                    if self.current_token.kind == TokenType::RBRACE {
+                        self.next_token();
+                    }
+                }
             }
-            // jumps over the semicolon
+        }
+
+        return (program, std::mem::take(&mut self.errors));
+    }
+
+    /// Panic-mode recovery after a statement failed to parse.
+    /// Skips tokens up to the start of the next statement: past a `;`, or onto a
+    /// `let`, `return`, `fn` or the `}` closing the enclosing block.
+    /// Always stops at EOF, and always moves past `start`, where the failed statement began
+    fn synchronize(&mut self, start: token::Span) {
+        if self.current_token.span == start {
             self.next_token();
         }
-        return Some(program);
+
+        loop {
+            match self.current_token.kind {
+                TokenType::EOF
+                | TokenType::RBRACE
+                | TokenType::LET
+                | TokenType::RETURN
+                | TokenType::FUNCTION => return,
+                TokenType::SEMICOLON => {
+                    self.next_token();
+                    return;
+                }
+                _ => self.next_token(),
+            }
+        }
     }
 
     fn parse_statement(&mut self) -> Option<ast::Statement> {
         match self.current_token.kind {
             token::TokenType::LET => return self.parse_let_statement(),
             token::TokenType::RETURN => return self.parse_return_statement(),
-            _ => {
-                println!(
-                    "[WARN]: Parsing error: Unknown token type: {:?}",
-                    self.current_token.kind
-                );
-                return None;
-            }
+            _ => return self.parse_expression_statement(),
         }
     }
 
@@ -91,63 +266,537 @@ impl Parser {
     /// Leaves the parser with the current token as the SEMICOLON
     /// make sure to call next_token() after this function
     fn parse_let_statement(&mut self) -> Option<ast::Statement> {
-        let let_token = self.current_token.clone();
+        let let_token = self.current_token.clone().into_owned();
 
-        self.expect_peek(TokenType::IDENT("".to_string()));
+        if !self.expect_peek(TokenType::IDENT) {
+            return None;
+        }
 
-        let ident = Identifier {
-            name: self.current_token.literal.clone(),
-        };
+        let ident = self.current_identifier();
 
         if !self.expect_peek(TokenType::ASSIGN) {
             return None;
         }
 
-        let stmt = ast::LetStatement {
-            token: let_token,
-            name: ident,
-            //TODO: skipping over the expression for now
-            value: ast::Expression::Literal(ast::Literal {
-                value: "0".to_string(),
-            }),
-        };
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
 
-        while self.current_token.kind != TokenType::SEMICOLON {
+        if self.peek_token.kind == TokenType::SEMICOLON {
             self.next_token();
         }
 
-        //println!("[INFO]: Current Token: {:?}", self.current_token);
-        //println!("[INFO]: Peek Token: {:?}", self.peek_token);
+        let stmt = ast::LetStatement {
+            span: let_token.span.to(self.current_token.span),
+            token: let_token,
+            name: ident,
+            value,
+        };
 
-        //println!("[INFO]: Let Statement: {:?}", stmt);
         return Some(ast::Statement::Let(stmt));
     }
 
+    /// Parses a return statement, the current token should be the RETURN keyword
+    ///
+    /// Leaves the parser on the last token of the statement
     fn parse_return_statement(&mut self) -> Option<ast::Statement> {
-        let return_token = self.current_token.clone();
+        let return_token = self.current_token.clone().into_owned();
 
         self.next_token();
-        // TODO: skipping over the expression for now
-        while !TokenType::variant_eq(&self.current_token.kind, &TokenType::SEMICOLON) {
+        let return_value = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token.kind == TokenType::SEMICOLON {
             self.next_token();
         }
 
         let stmt = ast::ReturnStatement {
+            span: return_token.span.to(self.current_token.span),
             token: return_token,
-            return_value: ast::Expression::Literal(ast::Literal {
-                value: "0".to_string(),
-            }),
+            return_value,
         };
 
         return Some(ast::Statement::Return(stmt));
     }
+
+    /// Parses a bare expression used as a statement, e.g. `add(1, 2);` or `x + y`
+    /// The trailing semicolon is optional
+    ///
+    /// Leaves the parser on the last token of the statement
+    fn parse_expression_statement(&mut self) -> Option<ast::Statement> {
+        let token = self.current_token.clone().into_owned();
+        let expression = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token.kind == TokenType::SEMICOLON {
+            self.next_token();
+        }
+
+        let stmt = ast::ExpressionStatement {
+            span: token.span.to(self.current_token.span),
+            token,
+            expression,
+        };
+
+        return Some(ast::Statement::Expression(stmt));
+    }
+
+    /// Parses statements up to the closing RBRACE
+    /// The current token should be the opening LBRACE
+    ///
+    /// Leaves the parser with the current token as the RBRACE
+    fn parse_block_statement(&mut self) -> Option<ast::BlockStatement> {
+        let block_token = self.current_token.clone().into_owned();
+        let mut statements = Vec::new();
+
+        self.next_token();
+
+        while self.current_token.kind != TokenType::RBRACE
+            && self.current_token.kind != TokenType::EOF
+        {
+            let start = self.current_token.span;
+            match self.parse_statement() {
+                Some(stmt) => {
+                    statements.push(stmt);
+                    self.next_token();
+                }
+                None => self.synchronize(start),
+            }
+        }
+
+        if self.current_token.kind == TokenType::EOF {
+            self.errors.push(ParseError::UnterminatedBlock {
+                span: block_token.span,
+            });
+            return None;
+        }
+
+        return Some(ast::BlockStatement {
+            span: block_token.span.to(self.current_token.span),
+            token: block_token,
+            statements,
+        });
+    }
+
+    /// Pratt parser entry point. Parses the expression starting at the current token,
+    /// folding infix operators into it for as long as they bind tighter than `precedence`
+    ///
+    /// Leaves the parser on the last token of the expression
+    fn parse_expression(&mut self, precedence: Precedence) -> Option<ast::Expression> {
+        if self.depth >= MAX_NESTING {
+            self.errors.push(ParseError::NestedTooDeeply {
+                span: self.current_token.span,
+            });
+            return None;
+        }
+
+        // Even below the limit a nested expression takes a lot of stack in debug builds,
+        // so grow it on the heap rather than overflow a small thread stack
+        self.depth += 1;
+        let expression = stacker::maybe_grow(RED_ZONE, STACK_SEGMENT, || {
+            self.parse_nested_expression(precedence)
+        });
+        self.depth -= 1;
+
+        return expression;
+    }
+
+    fn parse_nested_expression(&mut self, precedence: Precedence) -> Option<ast::Expression> {
+        let prefix = match prefix_parse_fn(&self.current_token.kind) {
+            Some(prefix) => prefix,
+            None => {
+                self.no_prefix_parse_fn_error();
+                return None;
+            }
+        };
+
+        let mut left = prefix(self)?;
+
+        while self.peek_token.kind != TokenType::SEMICOLON && precedence < self.peek_precedence() {
+            let infix = match infix_parse_fn(&self.peek_token.kind) {
+                Some(infix) => infix,
+                None => return Some(left),
+            };
+
+            self.next_token();
+            left = infix(self, left)?;
+        }
+
+        return Some(left);
+    }
+
+    fn parse_identifier(&mut self) -> Option<ast::Expression> {
+        return Some(ast::Expression::Identifier(self.current_identifier()));
+    }
+
+    fn parse_integer_literal(&mut self) -> Option<ast::Expression> {
+        let value = match self.current_token.kind {
+            TokenType::INT(value) => value,
+            _ => return None,
+        };
+
+        return Some(ast::Expression::Integer(ast::IntegerLiteral {
+            token: self.current_token.clone().into_owned(),
+            span: self.current_token.span,
+            value,
+        }));
+    }
+
+    fn parse_float_literal(&mut self) -> Option<ast::Expression> {
+        let value = match self.current_token.kind {
+            TokenType::FLOAT(value) => value,
+            _ => return None,
+        };
+
+        return Some(ast::Expression::Float(ast::FloatLiteral {
+            token: self.current_token.clone().into_owned(),
+            span: self.current_token.span,
+            value,
+        }));
+    }
+
+    fn parse_string_literal(&mut self) -> Option<ast::Expression> {
+        let value = match &self.current_token.kind {
+            TokenType::STRING(value) => value.clone(),
+            _ => return None,
+        };
+
+        return Some(ast::Expression::String(ast::StringLiteral {
+            token: self.current_token.clone().into_owned(),
+            span: self.current_token.span,
+            value,
+        }));
+    }
+
+    fn parse_boolean(&mut self) -> Option<ast::Expression> {
+        return Some(ast::Expression::Boolean(ast::BooleanLiteral {
+            token: self.current_token.clone().into_owned(),
+            span: self.current_token.span,
+            value: self.current_token.kind == TokenType::TRUE,
+        }));
+    }
+
+    fn parse_prefix_expression(&mut self) -> Option<ast::Expression> {
+        if let Some(min) = self.parse_min_integer() {
+            return Some(min);
+        }
+
+        let token = self.current_token.clone().into_owned();
+        let operator = token.literal.to_string();
+
+        self.next_token();
+        let right = self.parse_expression(Precedence::Prefix)?;
+
+        return Some(ast::Expression::Prefix(ast::PrefixExpression {
+            span: token.span.to(right.span()),
+            token,
+            operator,
+            right: Box::new(right),
+        }));
+    }
+
+    /// The digits of `-9223372036854775808` are out of range on their own, so the
+    /// lexer rejects them. Only the minus makes them `i64::MIN`, and the two fold
+    /// into a single literal here
+    fn parse_min_integer(&mut self) -> Option<ast::Expression> {
+        let literal = match &self.peek_token.kind {
+            TokenType::ERROR(LexError::IntegerOutOfRange { literal, .. })
+                if self.current_token.kind == TokenType::MINUS && is_min_magnitude(literal) =>
+            {
+                format!("-{}", literal)
+            }
+            _ => return None,
+        };
+
+        let span = self.current_token.span.to(self.peek_token.span);
+        self.next_token();
+
+        return Some(ast::Expression::Integer(ast::IntegerLiteral {
+            token: token::Token {
+                kind: TokenType::INT(i64::MIN),
+                literal: literal.into(),
+                span,
+            },
+            span,
+            value: i64::MIN,
+        }));
+    }
+
+    fn parse_infix_expression(&mut self, left: ast::Expression) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+        let operator = token.literal.to_string();
+        let precedence = self.current_precedence();
+
+        self.next_token();
+        let right = self.parse_expression(precedence)?;
+
+        return Some(ast::Expression::Infix(ast::InfixExpression {
+            span: left.span().to(right.span()),
+            token,
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }));
+    }
+
+    /// Parses `name = value` and the compound forms like `name += value`.
+    /// Assignment binds weakest and groups to the right, `a = b = 1` sets both
+    fn parse_assign_expression(&mut self, target: ast::Expression) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+
+        let name = match target {
+            ast::Expression::Identifier(name) => name,
+            target => {
+                self.errors.push(ParseError::InvalidAssignmentTarget {
+                    span: target.span(),
+                });
+                return None;
+            }
+        };
+
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        return Some(ast::Expression::Assign(ast::AssignExpression {
+            span: name.span.to(value.span()),
+            operator: token.literal.to_string(),
+            token,
+            name,
+            value: Box::new(value),
+        }));
+    }
+
+    fn parse_grouped_expression(&mut self) -> Option<ast::Expression> {
+        self.next_token();
+        let expression = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::RPAREN) {
+            return None;
+        }
+        return Some(expression);
+    }
+
+    fn parse_if_expression(&mut self) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+
+        if !self.expect_peek(TokenType::LPAREN) {
+            return None;
+        }
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::RPAREN) {
+            return None;
+        }
+        if !self.expect_peek(TokenType::LBRACE) {
+            return None;
+        }
+        let consequence = self.parse_block_statement()?;
+
+        let mut alternative = None;
+        if self.peek_token.kind == TokenType::ELSE {
+            self.next_token();
+            if !self.expect_peek(TokenType::LBRACE) {
+                return None;
+            }
+            alternative = Some(self.parse_block_statement()?);
+        }
+
+        return Some(ast::Expression::If(ast::IfExpression {
+            span: token.span.to(self.current_token.span),
+            token,
+            condition: Box::new(condition),
+            consequence,
+            alternative,
+        }));
+    }
+
+    fn parse_function_literal(&mut self) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+
+        if !self.expect_peek(TokenType::LPAREN) {
+            return None;
+        }
+        let parameters = self.parse_function_parameters()?;
+
+        if !self.expect_peek(TokenType::LBRACE) {
+            return None;
+        }
+        let body = self.parse_block_statement()?;
+
+        return Some(ast::Expression::Function(ast::FunctionLiteral {
+            span: token.span.to(body.span),
+            token,
+            parameters,
+            body,
+        }));
+    }
+
+    /// Parses a comma separated list of identifiers
+    /// The current token should be the opening LPAREN
+    ///
+    /// Leaves the parser with the current token as the RPAREN
+    fn parse_function_parameters(&mut self) -> Option<Vec<Identifier>> {
+        let mut identifiers = Vec::new();
+
+        if self.peek_token.kind == TokenType::RPAREN {
+            self.next_token();
+            return Some(identifiers);
+        }
+
+        if !self.expect_peek(TokenType::IDENT) {
+            return None;
+        }
+        identifiers.push(self.current_identifier());
+
+        while self.peek_token.kind == TokenType::COMMA {
+            self.next_token();
+            if !self.expect_peek(TokenType::IDENT) {
+                return None;
+            }
+            identifiers.push(self.current_identifier());
+        }
+
+        if !self.expect_peek(TokenType::RPAREN) {
+            return None;
+        }
+        return Some(identifiers);
+    }
+
+    fn parse_call_expression(&mut self, function: ast::Expression) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+        let arguments = self.parse_expression_list(TokenType::RPAREN)?;
+
+        return Some(ast::Expression::Call(ast::CallExpression {
+            span: function.span().to(self.current_token.span),
+            token,
+            function: Box::new(function),
+            arguments,
+        }));
+    }
+
+    fn parse_array_literal(&mut self) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+        let elements = self.parse_expression_list(TokenType::RBRACKET)?;
+
+        return Some(ast::Expression::Array(ast::ArrayLiteral {
+            span: token.span.to(self.current_token.span),
+            token,
+            elements,
+        }));
+    }
+
+    /// Parses `{key: value, ...}`. A `{` only opens a block where the grammar
+    /// requires one, after `if`, `else` or a function's parameters, so any `{`
+    /// that starts an expression is a hash
+    fn parse_hash_literal(&mut self) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+        let mut pairs = Vec::new();
+
+        while self.peek_token.kind != TokenType::RBRACE {
+            self.next_token();
+            let key = self.parse_expression(Precedence::Lowest)?;
+
+            if !self.expect_peek(TokenType::COLON) {
+                return None;
+            }
+
+            self.next_token();
+            let value = self.parse_expression(Precedence::Lowest)?;
+            pairs.push((key, value));
+
+            if self.peek_token.kind != TokenType::RBRACE && !self.expect_peek(TokenType::COMMA) {
+                return None;
+            }
+        }
+
+        self.next_token();
+
+        return Some(ast::Expression::Hash(ast::HashLiteral {
+            span: token.span.to(self.current_token.span),
+            token,
+            pairs,
+        }));
+    }
+
+    fn parse_index_expression(&mut self, left: ast::Expression) -> Option<ast::Expression> {
+        let token = self.current_token.clone().into_owned();
+
+        self.next_token();
+        let index = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::RBRACKET) {
+            return None;
+        }
+
+        return Some(ast::Expression::Index(ast::IndexExpression {
+            span: left.span().to(self.current_token.span),
+            token,
+            left: Box::new(left),
+            index: Box::new(index),
+        }));
+    }
+
+    /// Parses a comma separated list of expressions, such as call arguments
+    /// or array elements. The current token should be the opening delimiter
+    ///
+    /// Leaves the parser with the current token as `end`
+    fn parse_expression_list(&mut self, end: TokenType) -> Option<Vec<ast::Expression>> {
+        let mut list = Vec::new();
+
+        if self.peek_token.kind == end {
+            self.next_token();
+            return Some(list);
+        }
+
+        self.next_token();
+        list.push(self.parse_expression(Precedence::Lowest)?);
+
+        while self.peek_token.kind == TokenType::COMMA {
+            self.next_token();
+            self.next_token();
+            list.push(self.parse_expression(Precedence::Lowest)?);
+        }
+
+        if !self.expect_peek(end) {
+            return None;
+        }
+        return Some(list);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lexer::LexError;
+    use crate::token::Span;
     use pretty_assertions::assert_eq;
 
+    /// Unwraps a parse result, failing the test with every parser error listed
+    fn check_parser_errors(result: Result<ast::Program, Vec<ParseError>>) -> ast::Program {
+        match result {
+            Ok(program) => program,
+            Err(errors) => {
+                let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                panic!(
+                    "Parser has {} errors:\n\t{}",
+                    errors.len(),
+                    messages.join("\n\t")
+                );
+            }
+        }
+    }
+
+    /// Parses `input` and returns the value of its single let statement
+    fn parse_let_value(input: &str) -> ast::Expression {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = check_parser_errors(parser.parse_program());
+        assert_eq!(program.statements.len(), 1, "input: {}", input);
+
+        match program.statements.into_iter().next().unwrap() {
+            ast::Statement::Let(stmt) => stmt.value,
+            stmt => panic!("Expected LetStatement, got {:?}", stmt),
+        }
+    }
+
     #[test]
     fn test_let_statements() {
         let input = r#"let x = 5;
@@ -155,20 +804,10 @@ mod tests {
         let foobar = 838383;
         "#;
 
-        let lexer = Lexer::new(input.to_string());
+        let lexer = Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let program = parser.parse_program();
-        assert!(program.is_some(), "Parsing error: Program is has no nodes");
-
-        assert!(
-            parser.errors().is_empty(),
-            "Parser has {} errors:\n\t{}",
-            parser.errors().len(),
-            parser.errors().join("\n\t")
-        );
-
-        let program = program.unwrap();
+        let program = check_parser_errors(parser.parse_program());
         assert_eq!(
             program.statements.len(),
             3,
@@ -176,7 +815,7 @@ mod tests {
             program.statements.len()
         );
 
-        let expected_identifiers = vec![("x", "5"), ("y", "10"), ("foobar", "838383")];
+        let expected_identifiers = [("x", "5"), ("y", "10"), ("foobar", "838383")];
 
         for (i, (exp_ident, exp_value)) in expected_identifiers.iter().enumerate() {
             let stmt = &program.statements[i];
@@ -184,6 +823,7 @@ mod tests {
             match stmt {
                 ast::Statement::Let(l_stmt) => {
                     assert_eq!(l_stmt.name.name, *exp_ident);
+                    assert_eq!(l_stmt.value.to_string(), *exp_value);
                 }
                 _ => panic!("Expected LetStatement, got {:?}", stmt),
             }
@@ -197,20 +837,10 @@ mod tests {
         return 993322;
         "#;
 
-        let lexer = Lexer::new(input.to_string());
+        let lexer = Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let program = parser.parse_program();
-        assert!(program.is_some(), "Parsing error: Program is has no nodes");
-
-        assert!(
-            parser.errors().is_empty(),
-            "Parser has {} errors:\n\t{}",
-            parser.errors().len(),
-            parser.errors().join("\n\t")
-        );
-
-        let program = program.unwrap();
+        let program = check_parser_errors(parser.parse_program());
         assert_eq!(
             program.statements.len(),
             3,
@@ -218,6 +848,8 @@ mod tests {
             program.statements.len()
         );
 
+        let expected_values = ["5", "10", "993322"];
+
         for (i, statement) in program.statements.iter().enumerate() {
             match statement {
                 ast::Statement::Return(stmt) => {
@@ -227,6 +859,7 @@ mod tests {
                         "Expected Return token, got {:?}",
                         stmt.token.kind
                     );
+                    assert_eq!(stmt.return_value.to_string(), expected_values[i]);
                 }
                 _ => {
                     panic!("Expected ReturnStatement, got {:?}", statement);
@@ -234,4 +867,705 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_let_statement_errors() {
+        let input = "let = 5; let x 5;";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let errors = parser.parse_program().unwrap_err();
+
+        let span = |start, end, column| Span {
+            start,
+            end,
+            line: 1,
+            column,
+        };
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::UnexpectedToken {
+                    expected: TokenType::IDENT,
+                    found: TokenType::ASSIGN,
+                    span: span(4, 5, 5),
+                },
+                ParseError::UnexpectedToken {
+                    expected: TokenType::ASSIGN,
+                    found: TokenType::INT(5),
+                    span: span(15, 16, 16),
+                },
+            ]
+        );
+
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "expected identifier, found `=`",
+                "expected `=`, found integer",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recovers_from_independent_errors() {
+        let input = r#"let a = 1;
+        let = 2;
+        let b = 3;
+        let c 4;
+        add(1, 2;
+        let d = fn(x) { x + };
+        return );
+        let e = if (a) { 1 } else 2
+        let f = 6;
+        "#;
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let (program, errors) = parser.parse_program_partial();
+
+        let lines: Vec<usize> = errors.iter().map(|e| e.span().line).collect();
+        assert_eq!(lines, vec![2, 4, 5, 6, 7, 8], "errors: {:?}", errors);
+
+        let names: Vec<&str> = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                ast::Statement::Let(stmt) => Some(stmt.name.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["a", "b", "d", "f"]);
+    }
+
+    #[test]
+    fn test_recovery_inside_blocks() {
+        let input = "let f = fn() { let = 1; 2 }; let g = 3;";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let (program, errors) = parser.parse_program_partial();
+
+        assert_eq!(errors.len(), 1, "errors: {:?}", errors);
+        assert_eq!(program.statements.len(), 2);
+        match &program.statements[0] {
+            ast::Statement::Let(stmt) => match &stmt.value {
+                ast::Expression::Function(func) => {
+                    assert_eq!(func.body.statements.len(), 1);
+                    assert_eq!(func.body.statements[0].to_string(), "2;");
+                }
+                value => panic!("Expected FunctionLiteral, got {:?}", value),
+            },
+            stmt => panic!("Expected LetStatement, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_recovery_terminates_at_eof() {
+        let inputs = [
+            "let x = ",
+            "let x",
+            "return",
+            "let x = 5 let y = (",
+            "fn(x, ",
+            "if (x) { let y = ",
+            "}}}",
+            "let = = = =",
+        ];
+
+        for input in inputs.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let (_, errors) = parser.parse_program_partial();
+            assert!(!errors.is_empty(), "Expected errors for {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_unterminated_block() {
+        let input = "let f = fn(x) {\n  x + 1;";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let errors = parser.parse_program().unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![ParseError::UnterminatedBlock {
+                span: Span {
+                    start: 14,
+                    end: 15,
+                    line: 1,
+                    column: 15,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_illegal_token() {
+        let input = "let a = @;";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let errors = parser.parse_program().unwrap_err();
+
+        assert_eq!(
+            errors[0],
+            ParseError::IllegalToken {
+                literal: "@".to_string(),
+                span: Span {
+                    start: 8,
+                    end: 9,
+                    line: 1,
+                    column: 9,
+                },
+            }
+        );
+        assert_eq!(errors[0].to_string(), "illegal character `@`");
+    }
+
+    #[test]
+    fn test_string_literal() {
+        let value = parse_let_value(r#"let s = "hello\tworld";"#);
+        match value {
+            ast::Expression::String(string) => {
+                assert_eq!(string.value, "hello\tworld");
+                assert_eq!(string.token.literal, r#""hello\tworld""#);
+            }
+            other => panic!("Expected String, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_lexical_errors() {
+        let input = "let a = \"\\x\";\nlet b \"open";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let errors = parser.parse_program().unwrap_err();
+
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "unknown escape sequence `\\x`",
+                "unterminated string, expected `\"` before end of input",
+            ]
+        );
+        assert_eq!(errors[1].span().line, 2);
+        assert_eq!(errors[1].span().column, 7);
+        assert!(std::error::Error::source(&errors[0]).is_none());
+    }
+
+    #[test]
+    fn test_comments() {
+        let input = "let a = 1; // one
+        /* a block
+           comment */ let b = a;";
+
+        for lexer in [Lexer::new(input), Lexer::new(input).with_comments()] {
+            let mut parser = Parser::new(lexer);
+            let program = check_parser_errors(parser.parse_program());
+            assert_eq!(program.to_string(), "let a = 1;\nlet b = a;\n");
+        }
+    }
+
+    #[test]
+    fn test_invalid_integer() {
+        let input = "10000000000000000000";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let errors = parser.parse_program().unwrap_err();
+
+        match &errors[..] {
+            [ParseError::Lexical {
+                error: LexError::IntegerOutOfRange { literal, .. },
+            }] => assert_eq!(literal, "10000000000000000000"),
+            _ => panic!(
+                "Expected a single IntegerOutOfRange error, got {:?}",
+                errors
+            ),
+        }
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+
+        // The statement's own expression counts as the first level
+        let program = parse(&nested(MAX_NESTING - 1)).unwrap();
+        assert_eq!(program.to_string(), "1;\n");
+
+        let arrays = format!("{}1{}", "[".repeat(100), "]".repeat(100));
+        let program = parse(&format!("-{}", arrays)).unwrap();
+        assert_eq!(program.to_string(), format!("(-{});\n", arrays));
+
+        for depth in [MAX_NESTING, 5_000, 50_000] {
+            let errors = parse(&nested(depth)).unwrap_err();
+            assert_eq!(
+                errors[0],
+                ParseError::NestedTooDeeply {
+                    span: Span {
+                        start: MAX_NESTING,
+                        end: MAX_NESTING + 1,
+                        line: 1,
+                        column: MAX_NESTING + 1,
+                    }
+                },
+                "depth: {}",
+                depth
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_integer() {
+        let tests = [
+            ("-9223372036854775808", "-9223372036854775808"),
+            (
+                "-9_223_372_036_854_775_808 * 2",
+                "(-9223372036854775808 * 2)",
+            ),
+            ("-0x8000_0000_0000_0000", "-9223372036854775808"),
+            ("--9223372036854775808", "(--9223372036854775808)"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let program = parse(input).unwrap();
+            assert_eq!(program.to_string(), format!("{};\n", expected));
+        }
+
+        match parse_let_value("let min = - 9223372036854775808;") {
+            ast::Expression::Integer(int) => {
+                assert_eq!(int.value, i64::MIN);
+                assert_eq!(int.token.literal, "-9223372036854775808");
+                assert_eq!((int.span.start, int.span.end), (10, 31));
+            }
+            other => panic!("Expected Integer, got {:?}", other),
+        }
+
+        // Only a prefix minus makes the magnitude fit
+        let tests = [
+            "1 - 9223372036854775808",
+            "-9223372036854775809",
+            "!9223372036854775808",
+        ];
+
+        for input in tests.iter() {
+            let errors = parse(input).unwrap_err();
+            assert!(
+                matches!(
+                    &errors[..],
+                    [ParseError::Lexical {
+                        error: LexError::IntegerOutOfRange { .. }
+                    }]
+                ),
+                "input: {}, errors: {:?}",
+                input,
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn test_expression_statements() {
+        let input = r#"add(1, 2);
+        x + y;
+        foobar;
+        5 + 5"#;
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = check_parser_errors(parser.parse_program());
+
+        let expected = ["add(1, 2)", "(x + y)", "foobar", "(5 + 5)"];
+        assert_eq!(program.statements.len(), expected.len());
+
+        for (statement, expected) in program.statements.iter().zip(expected.iter()) {
+            match statement {
+                ast::Statement::Expression(stmt) => {
+                    assert_eq!(stmt.expression.to_string(), *expected)
+                }
+                _ => panic!("Expected ExpressionStatement, got {:?}", statement),
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_expression_statements() {
+        let value = parse_let_value("let a = if (x < y) { x } else { y; z };");
+
+        match value {
+            ast::Expression::If(if_expr) => {
+                assert_eq!(if_expr.consequence.statements.len(), 1);
+                let alternative = if_expr.alternative.expect("Expected an else branch");
+                assert_eq!(alternative.statements.len(), 2);
+                match &alternative.statements[1] {
+                    ast::Statement::Expression(stmt) => {
+                        assert_eq!(stmt.expression.to_string(), "z")
+                    }
+                    stmt => panic!("Expected ExpressionStatement, got {:?}", stmt),
+                }
+            }
+            value => panic!("Expected IfExpression, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_prefix_expressions() {
+        let tests = [
+            ("let a = !5;", "(!5)"),
+            ("let a = -15;", "(-15)"),
+            ("let a = !true;", "(!true)"),
+            ("let a = !false;", "(!false)"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let value = parse_let_value(input);
+            match &value {
+                ast::Expression::Prefix(_) => {}
+                _ => panic!("Expected PrefixExpression, got {:?}", value),
+            }
+            assert_eq!(value.to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_infix_expressions() {
+        let operators = ["+", "-", "*", "/", ">", "<", "==", "!="];
+
+        for operator in operators.iter() {
+            let input = format!("let a = 5 {} 6;", operator);
+            match parse_let_value(&input) {
+                ast::Expression::Infix(infix) => {
+                    assert_eq!(infix.operator, *operator);
+                    assert_eq!(infix.left.to_string(), "5");
+                    assert_eq!(infix.right.to_string(), "6");
+                }
+                value => panic!("Expected InfixExpression, got {:?}", value),
+            }
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let tests = [
+            ("-a * b", "((-a) * b)"),
+            ("!-a", "(!(-a))"),
+            ("a + b + c", "((a + b) + c)"),
+            ("a + b - c", "((a + b) - c)"),
+            ("a * b * c", "((a * b) * c)"),
+            ("a * b / c", "((a * b) / c)"),
+            ("a + b / c", "(a + (b / c))"),
+            ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
+            ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))"),
+            ("5 < 4 != 3 > 4", "((5 < 4) != (3 > 4))"),
+            (
+                "3 + 4 * 5 == 3 * 1 + 4 * 5",
+                "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))",
+            ),
+            ("3 > 5 == false", "((3 > 5) == false)"),
+            ("1 + (2 + 3) + 4", "((1 + (2 + 3)) + 4)"),
+            ("(5 + 5) * 2", "((5 + 5) * 2)"),
+            ("-(5 + 5)", "(-(5 + 5))"),
+            ("!(true == true)", "(!(true == true))"),
+            ("a + add(b * c) + d", "((a + add((b * c))) + d)"),
+            (
+                "add(a, b, 1, 2 * 3, 4 + 5, add(6, 7 * 8))",
+                "add(a, b, 1, (2 * 3), (4 + 5), add(6, (7 * 8)))",
+            ),
+            (
+                "add(a + b + c * d / f + g)",
+                "add((((a + b) + ((c * d) / f)) + g))",
+            ),
+            ("a % b * c", "((a % b) * c)"),
+            ("a + b % c", "(a + (b % c))"),
+            ("a <= b == c >= d", "((a <= b) == (c >= d))"),
+            ("a || b && c", "(a || (b && c))"),
+            ("a && b || c", "((a && b) || c)"),
+            ("!a && b == c || d", "(((!a) && (b == c)) || d)"),
+            ("x = y = 1 + 2", "(x = (y = (1 + 2)))"),
+            ("x += y * 2 || z", "(x += ((y * 2) || z))"),
+            ("f(x -= 1, y /= 2)", "f((x -= 1), (y /= 2))"),
+            (
+                "a * [1, 2, 3, 4][b * c] * d",
+                "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+            ),
+            (
+                "add(a * b[2], b[1], 2 * [1, 2][1])",
+                "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
+            ),
+            ("-xs[0]", "(-(xs[0]))"),
+            ("f(x)[0](y)", "(f(x)[0])(y)"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let value = parse_let_value(&format!("let a = {};", input));
+            assert_eq!(value.to_string(), *expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_assign_expression() {
+        let tests = [
+            ("x = 5;", "x", "=", "5"),
+            ("x += y;", "x", "+=", "y"),
+            ("total -= 1", "total", "-=", "1"),
+            ("n *= n", "n", "*=", "n"),
+            ("half /= 2.0", "half", "/=", "2.0"),
+        ];
+
+        for (input, name, operator, value) in tests.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let program = check_parser_errors(parser.parse_program());
+
+            match &program.statements[..] {
+                [ast::Statement::Expression(ast::ExpressionStatement {
+                    expression: ast::Expression::Assign(assign),
+                    ..
+                })] => {
+                    assert_eq!(assign.name.name, *name);
+                    assert_eq!(assign.operator, *operator);
+                    assert_eq!(assign.value.to_string(), *value);
+                    assert_eq!(assign.span.start, 0);
+                }
+                _ => panic!("Expected a single assignment, got {:?}", program.statements),
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        let input = "1 + x = 2;\nf() += 1;\nx = 3;";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let (program, errors) = parser.parse_program_partial();
+
+        let spans: Vec<(usize, usize)> = errors
+            .iter()
+            .map(|e| (e.span().start, e.span().end))
+            .collect();
+        assert_eq!(spans, [(0, 5), (11, 14)]);
+        assert_eq!(
+            errors[0].to_string(),
+            "invalid assignment target, expected an identifier"
+        );
+        assert_eq!(program.to_string(), "(x = 3);\n");
+    }
+
+    #[test]
+    fn test_array_literal() {
+        match parse_let_value("let xs = [1, 2 * 2, 3 + 3];") {
+            ast::Expression::Array(array) => {
+                let elements: Vec<String> = array.elements.iter().map(|e| e.to_string()).collect();
+                assert_eq!(elements, ["1", "(2 * 2)", "(3 + 3)"]);
+                assert_eq!((array.span.start, array.span.end), (9, 26));
+            }
+            other => panic!("Expected Array, got {:?}", other),
+        }
+
+        match parse_let_value("let xs = [];") {
+            ast::Expression::Array(array) => assert!(array.elements.is_empty()),
+            other => panic!("Expected Array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_index_expression() {
+        match parse_let_value("let x = xs[1 + 1];") {
+            ast::Expression::Index(index) => {
+                assert_eq!(index.left.to_string(), "xs");
+                assert_eq!(index.index.to_string(), "(1 + 1)");
+                assert_eq!((index.span.start, index.span.end), (8, 17));
+            }
+            other => panic!("Expected Index, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unclosed_array() {
+        let lexer = Lexer::new("let xs = [1, 2;");
+        let mut parser = Parser::new(lexer);
+        let errors = parser.parse_program().unwrap_err();
+
+        assert_eq!(errors[0].to_string(), "expected `]`, found `;`");
+    }
+
+    #[test]
+    fn test_hash_literal() {
+        match parse_let_value(r#"let h = {"one": 1, two: 1 + 1, 3: [3], true: {}};"#) {
+            ast::Expression::Hash(hash) => {
+                let pairs: Vec<(String, String)> = hash
+                    .pairs
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect();
+                assert_eq!(
+                    pairs,
+                    [
+                        (r#""one""#.to_string(), "1".to_string()),
+                        ("two".to_string(), "(1 + 1)".to_string()),
+                        ("3".to_string(), "[3]".to_string()),
+                        ("true".to_string(), "{}".to_string()),
+                    ]
+                );
+                assert_eq!((hash.span.start, hash.span.end), (8, 48));
+            }
+            other => panic!("Expected Hash, got {:?}", other),
+        }
+
+        match parse_let_value("let h = {};") {
+            ast::Expression::Hash(hash) => assert!(hash.pairs.is_empty()),
+            other => panic!("Expected Hash, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hash_errors() {
+        let tests = [
+            ("let h = {1 2};", "expected `:`, found integer"),
+            ("let h = {1: 2 3: 4};", "expected `,`, found integer"),
+            ("let h = {1: 2;", "expected `,`, found `;`"),
+        ];
+
+        for (input, expected) in tests.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let errors = parser.parse_program().unwrap_err();
+
+            assert_eq!(errors[0].to_string(), *expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_if_expression() {
+        let value = parse_let_value("let a = if (x < y) { return x; } else { return y; };");
+
+        match value {
+            ast::Expression::If(if_expr) => {
+                assert_eq!(if_expr.condition.to_string(), "(x < y)");
+                assert_eq!(if_expr.consequence.statements.len(), 1);
+                match &if_expr.consequence.statements[0] {
+                    ast::Statement::Return(stmt) => assert_eq!(stmt.return_value.to_string(), "x"),
+                    stmt => panic!("Expected ReturnStatement, got {:?}", stmt),
+                }
+
+                let alternative = if_expr.alternative.expect("Expected an else branch");
+                assert_eq!(alternative.statements.len(), 1);
+                match &alternative.statements[0] {
+                    ast::Statement::Return(stmt) => assert_eq!(stmt.return_value.to_string(), "y"),
+                    stmt => panic!("Expected ReturnStatement, got {:?}", stmt),
+                }
+            }
+            value => panic!("Expected IfExpression, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_function_literal() {
+        let value = parse_let_value("let add = fn(x, y) { return x + y; };");
+
+        match value {
+            ast::Expression::Function(func) => {
+                let parameters: Vec<&str> =
+                    func.parameters.iter().map(|p| p.name.as_str()).collect();
+                assert_eq!(parameters, vec!["x", "y"]);
+
+                assert_eq!(func.body.statements.len(), 1);
+                match &func.body.statements[0] {
+                    ast::Statement::Return(stmt) => {
+                        assert_eq!(stmt.return_value.to_string(), "(x + y)")
+                    }
+                    stmt => panic!("Expected ReturnStatement, got {:?}", stmt),
+                }
+            }
+            value => panic!("Expected FunctionLiteral, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_function_parameters() {
+        let tests: [(&str, Vec<&str>); 3] = [
+            ("let f = fn() {};", vec![]),
+            ("let f = fn(x) {};", vec!["x"]),
+            ("let f = fn(x, y, z) {};", vec!["x", "y", "z"]),
+        ];
+
+        for (input, expected) in tests.iter() {
+            match parse_let_value(input) {
+                ast::Expression::Function(func) => {
+                    let parameters: Vec<&str> =
+                        func.parameters.iter().map(|p| p.name.as_str()).collect();
+                    assert_eq!(parameters, *expected);
+                }
+                value => panic!("Expected FunctionLiteral, got {:?}", value),
+            }
+        }
+    }
+
+    #[test]
+    fn test_call_expression() {
+        match parse_let_value("let a = add(1, 2 * 3, 4 + 5);") {
+            ast::Expression::Call(call) => {
+                assert_eq!(call.function.to_string(), "add");
+                let arguments: Vec<String> = call.arguments.iter().map(|a| a.to_string()).collect();
+                assert_eq!(arguments, vec!["1", "(2 * 3)", "(4 + 5)"]);
+            }
+            value => panic!("Expected CallExpression, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_node_spans() {
+        let input = "let add = fn(x, y) {\n  x + y\n};\nadd(1, -2);";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let program = check_parser_errors(parser.parse_program());
+
+        let slice = |span: Span| &input[span.start..span.end];
+
+        match &program.statements[0] {
+            ast::Statement::Let(stmt) => {
+                assert_eq!(slice(stmt.span), "let add = fn(x, y) {\n  x + y\n};");
+                assert_eq!(slice(stmt.name.span), "add");
+                assert_eq!((stmt.name.span.line, stmt.name.span.column), (1, 5));
+
+                match &stmt.value {
+                    ast::Expression::Function(func) => {
+                        assert_eq!(slice(func.span), "fn(x, y) {\n  x + y\n}");
+                        assert_eq!(slice(func.parameters[1].span), "y");
+                        assert_eq!(slice(func.body.span), "{\n  x + y\n}");
+
+                        let body = func.body.statements[0].span();
+                        assert_eq!(slice(body), "x + y");
+                        assert_eq!((body.line, body.column), (2, 3));
+                    }
+                    value => panic!("Expected FunctionLiteral, got {:?}", value),
+                }
+            }
+            stmt => panic!("Expected LetStatement, got {:?}", stmt),
+        }
+
+        match &program.statements[1] {
+            ast::Statement::Expression(stmt) => {
+                assert_eq!(slice(stmt.span), "add(1, -2);");
+                assert_eq!(slice(stmt.expression.span()), "add(1, -2)");
+                assert_eq!((stmt.span.line, stmt.span.column), (4, 1));
+
+                match &stmt.expression {
+                    ast::Expression::Call(call) => {
+                        assert_eq!(slice(call.arguments[1].span()), "-2")
+                    }
+                    value => panic!("Expected CallExpression, got {:?}", value),
+                }
+            }
+            stmt => panic!("Expected ExpressionStatement, got {:?}", stmt),
+        }
+    }
 }
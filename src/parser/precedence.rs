@@ -0,0 +1,43 @@
+use crate::token::TokenType;
+
+/// Binding power of operators, from weakest to strongest.
+/// The derived ordering is what the Pratt loop compares against.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub enum Precedence {
+    Lowest,
+    Assign,      // = or +=, right associative
+    LogicalOr,   // ||
+    LogicalAnd,  // &&
+    Equals,      // ==
+    LessGreater, // > or <
+    Sum,         // +
+    Product,     // * or %
+    Prefix,      // -X or !X
+    Call,        // myFunction(X)
+    Index,       // array[index]
+}
+
+impl Precedence {
+    /// Returns the precedence a token has when used as an infix operator
+    pub fn of(kind: &TokenType) -> Precedence {
+        match kind {
+            TokenType::ASSIGN
+            | TokenType::PLUSASSIGN
+            | TokenType::MINUSASSIGN
+            | TokenType::ASTERISKASSIGN
+            | TokenType::SLASHASSIGN => Precedence::Assign,
+            TokenType::OR => Precedence::LogicalOr,
+            TokenType::AND => Precedence::LogicalAnd,
+            TokenType::EQUAL | TokenType::NOTEQUAL => Precedence::Equals,
+            TokenType::LESSTHAN
+            | TokenType::GREATERTHAN
+            | TokenType::LESSEQUAL
+            | TokenType::GREATEREQUAL => Precedence::LessGreater,
+            TokenType::PLUS | TokenType::MINUS => Precedence::Sum,
+            TokenType::ASTERISK | TokenType::SLASH | TokenType::PERCENT => Precedence::Product,
+            TokenType::LPAREN => Precedence::Call,
+            TokenType::LBRACKET => Precedence::Index,
+            _ => Precedence::Lowest,
+        }
+    }
+}
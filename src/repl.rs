@@ -1,28 +1,227 @@
 use std::io::{self, stdout, Write};
 
-use crate::lexer::Lexer;
-use crate::token::TokenType;
+use junkie::ast::{self, printer};
+use junkie::diagnostics::{Diagnostic, Renderer};
+use junkie::{Interpreter, Lexer, Parser};
+
+const ORIGIN: &str = "<repl>";
+
+const HELP: &str = "Meta-commands:
+    :eval      evaluate input (default)
+    :tokens    print the token stream
+    :ast       print the syntax tree
+    :sexp      print the syntax tree as S-expressions
+    :help      print this message
+
+Follow a mode command with source, e.g. `:ast 1 + 2`, to use that mode for a single line.
+Type `exit` to quit.";
+
+/// What the REPL does with each line of input
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Eval,
+    Tokens,
+    Ast,
+    Sexp,
+}
+
+impl Mode {
+    fn from_command(command: &str) -> Option<Mode> {
+        match command {
+            ":eval" => Some(Mode::Eval),
+            ":tokens" => Some(Mode::Tokens),
+            ":ast" => Some(Mode::Ast),
+            ":sexp" => Some(Mode::Sexp),
+            _ => None,
+        }
+    }
+}
+
+struct Session {
+    mode: Mode,
+    interpreter: Interpreter,
+    renderer: Renderer,
+    /// Every line evaluated so far. A function defined on an earlier line
+    /// raises errors pointing into that line, so they render against all of it
+    source: String,
+}
+
+pub fn start(color: bool) {
+    let mut session = Session::new(color);
 
-pub fn start() {
     loop {
         let mut input: String = String::new();
         print!(">> ");
         stdout().flush().expect("Error flushing stdout.");
-        io::stdin()
+        let read = io::stdin()
             .read_line(&mut input)
             .expect("Failed to read line");
 
-        if input.trim() == "exit" {
+        if read == 0 || input.trim() == "exit" {
             break;
         }
 
-        let mut lexer = Lexer::new(input);
-        let mut token = lexer.next_token();
+        if input.trim_start().starts_with(':') {
+            session.meta_command(input.trim());
+            continue;
+        }
+
+        session.run(session.mode, &input);
+    }
+}
+
+impl Session {
+    fn new(color: bool) -> Session {
+        return Session {
+            mode: Mode::Eval,
+            interpreter: Interpreter::new(),
+            renderer: Renderer::new(color),
+            source: String::new(),
+        };
+    }
+
+    fn meta_command(&mut self, line: &str) {
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        if command == ":help" {
+            println!("{}", HELP);
+            return;
+        }
+
+        match Mode::from_command(command) {
+            Some(mode) if rest.trim().is_empty() => {
+                self.mode = mode;
+                println!("mode: {}", &command[1..]);
+            }
+            Some(mode) => self.run(mode, rest),
+            None => println!("Unknown command `{}`, try :help", command),
+        }
+    }
+
+    fn run(&mut self, mode: Mode, input: &str) {
+        match mode {
+            Mode::Tokens => {
+                for token in Lexer::new(input) {
+                    println!("Type: {:?}  Literal: {}", token.kind, token.literal);
+                }
+            }
+            Mode::Ast => {
+                if let Some(program) = self.parse(input) {
+                    print!("{}", printer::tree(&program));
+                }
+            }
+            Mode::Sexp => {
+                if let Some(program) = self.parse(input) {
+                    print!("{}", printer::sexp(&program));
+                }
+            }
+            Mode::Eval => print!("{}", self.eval(input)),
+        }
+    }
+
+    /// Evaluates `input` as the next line of the session and returns what to print
+    fn eval(&mut self, input: &str) -> String {
+        let start = self.source.len();
+        self.source.push_str(input);
+        if !input.ends_with('\n') {
+            self.source.push('\n');
+        }
+
+        let mut parser = Parser::new(Lexer::resume(&self.source, start));
+        let program = match parser.parse_program() {
+            Ok(program) => program,
+            Err(errors) => {
+                return errors
+                    .iter()
+                    .map(|error| {
+                        let diagnostic = Diagnostic::from(error);
+                        self.renderer.render(&diagnostic, &self.source, ORIGIN)
+                    })
+                    .collect();
+            }
+        };
+
+        match self.interpreter.eval_program(&program) {
+            Ok(result) => return format!("{}\n", result),
+            Err(error) => {
+                let diagnostic = Diagnostic::from(&error);
+                return self.renderer.render(&diagnostic, &self.source, ORIGIN);
+            }
+        }
+    }
+
+    /// Parses `input`, printing diagnostics for any errors
+    fn parse(&self, input: &str) -> Option<ast::Program> {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
 
-        while token.kind != TokenType::EOF {
-            println!("Type: {:?}  Literal: {}", token.kind, token.literal);
-            token = lexer.next_token();
+        match parser.parse_program() {
+            Ok(program) => return Some(program),
+            Err(errors) => {
+                for error in errors.iter() {
+                    let diagnostic = Diagnostic::from(error);
+                    print!("{}", self.renderer.render(&diagnostic, input, ORIGIN));
+                }
+                return None;
+            }
         }
-        print!("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_eval_keeps_bindings() {
+        let mut session = Session::new(false);
+
+        assert_eq!(session.eval("let x = 20;\n"), "null\n");
+        assert_eq!(session.eval("x + 1\n"), "21\n");
+        assert_eq!(session.eval("x * 2"), "40\n");
+    }
+
+    #[test]
+    fn test_errors_point_into_earlier_lines() {
+        let mut session = Session::new(false);
+        session.eval("let g = fn() { 1 / 0 };\n");
+
+        assert_eq!(
+            session.eval("g()\n"),
+            "error: division by zero
+ --> <repl>:1:16
+  |
+1 | let g = fn() { 1 / 0 };
+  |                ^^^^^
+"
+        );
+
+        session.eval("let f = fn(x) { x + true };\n");
+        assert_eq!(
+            session.eval("\"éééééééééééééééééé\"; f(1)\n"),
+            "error: type mismatch: INTEGER + BOOLEAN
+ --> <repl>:3:17
+  |
+3 | let f = fn(x) { x + true };
+  |                 ^^^^^^^^
+"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_current_line() {
+        let mut session = Session::new(false);
+        session.eval("let a = 1;\n");
+
+        assert_eq!(
+            session.eval("let b 2;\n"),
+            "error: expected `=`, found integer
+ --> <repl>:2:7
+  |
+2 | let b 2;
+  |       ^ expected `=` here
+"
+        );
     }
 }
@@ -1,10 +1,22 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::lexer::LexError;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     ILLEGAL,
     EOF,
 
-    IDENT(String),
-    INT(usize),
+    IDENT,
+    INT(i64),
+    FLOAT(f64),
+    /// Value of a string literal, with escapes already decoded
+    STRING(String),
+    /// A `//` or `/* */` comment, only produced by a lexer built `with_comments`
+    COMMENT,
+    /// Source the lexer rejected, e.g. an unterminated string
+    ERROR(LexError),
 
     PLUS,
     ASSIGN,
@@ -12,20 +24,34 @@ pub enum TokenType {
     BANG,
     ASTERISK,
     SLASH,
+    PERCENT,
+
+    PLUSASSIGN,
+    MINUSASSIGN,
+    ASTERISKASSIGN,
+    SLASHASSIGN,
 
     LESSTHAN,
     GREATERTHAN,
+    LESSEQUAL,
+    GREATEREQUAL,
 
     EQUAL,
     NOTEQUAL,
 
+    AND,
+    OR,
+
     COMMA,
     SEMICOLON,
+    COLON,
 
     LPAREN,
     RPAREN,
     LBRACE,
     RBRACE,
+    LBRACKET,
+    RBRACKET,
 
     FUNCTION,
     LET,
@@ -40,24 +66,119 @@ impl TokenType {
     pub fn variant_eq(&self, other: &Self) -> bool {
         use TokenType::*;
         match (self, other) {
-            (IDENT(_), IDENT(_)) => true,
             (INT(_), INT(_)) => true,
+            (FLOAT(_), FLOAT(_)) => true,
+            (STRING(_), STRING(_)) => true,
+            (ERROR(_), ERROR(_)) => true,
             _ => self == other,
         }
     }
 }
 
+impl fmt::Display for TokenType {
+    /// Human readable description, as used in error messages
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TokenType::*;
+        let symbol = match self {
+            ILLEGAL => return write!(f, "illegal token"),
+            EOF => return write!(f, "end of input"),
+            IDENT => return write!(f, "identifier"),
+            INT(_) => return write!(f, "integer"),
+            FLOAT(_) => return write!(f, "float"),
+            STRING(_) => return write!(f, "string"),
+            COMMENT => return write!(f, "comment"),
+            ERROR(_) => return write!(f, "invalid token"),
+
+            PLUS => "+",
+            ASSIGN => "=",
+            MINUS => "-",
+            BANG => "!",
+            ASTERISK => "*",
+            SLASH => "/",
+            PERCENT => "%",
+            PLUSASSIGN => "+=",
+            MINUSASSIGN => "-=",
+            ASTERISKASSIGN => "*=",
+            SLASHASSIGN => "/=",
+            LESSTHAN => "<",
+            GREATERTHAN => ">",
+            LESSEQUAL => "<=",
+            GREATEREQUAL => ">=",
+            EQUAL => "==",
+            NOTEQUAL => "!=",
+            AND => "&&",
+            OR => "||",
+            COMMA => ",",
+            SEMICOLON => ";",
+            COLON => ":",
+            LPAREN => "(",
+            RPAREN => ")",
+            LBRACE => "{",
+            RBRACE => "}",
+            LBRACKET => "[",
+            RBRACKET => "]",
+
+            FUNCTION => "fn",
+            LET => "let",
+            TRUE => "true",
+            FALSE => "false",
+            IF => "if",
+            ELSE => "else",
+            RETURN => "return",
+        };
+        write!(f, "`{}`", symbol)
+    }
+}
+
+/// Location of a piece of source text.
+/// `start` and `end` are byte offsets (end exclusive), `line` and `column`
+/// are 1-based and point at the first character
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// Returns a span covering everything from the start of `self` to the end of `other`
+    pub fn to(&self, other: Span) -> Span {
+        return Span {
+            start: self.start,
+            end: other.end,
+            line: self.line,
+            column: self.column,
+        };
+    }
+}
+
+/// A token whose literal borrows from the source it was lexed from
 #[derive(PartialEq, Debug, Clone)]
-pub struct Token {
+pub struct Token<'src> {
     pub kind: TokenType,
-    pub literal: String,
+    pub literal: Cow<'src, str>,
+    pub span: Span,
 }
 
-impl Token {
-    pub fn new_token(token_type: TokenType, ch: String) -> Token {
+/// A token that owns its literal, for keeping tokens around after the source is gone
+pub type OwnedToken = Token<'static>;
+
+impl<'src> Token<'src> {
+    pub fn new_token(token_type: TokenType, literal: &'src str) -> Token<'src> {
         return Token {
             kind: token_type,
-            literal: ch.to_string(),
+            literal: Cow::Borrowed(literal),
+            span: Span::default(),
+        };
+    }
+
+    /// Copies the literal out of the source, detaching the token from it
+    pub fn into_owned(self) -> OwnedToken {
+        return Token {
+            kind: self.kind,
+            literal: Cow::Owned(self.literal.into_owned()),
+            span: self.span,
         };
     }
 }
@@ -1,8 +1,18 @@
-pub fn is_letter(ch: char) -> bool {
-    return 'a' <= ch && ch <= 'z' || 'A' <= ch && ch <= 'Z' || ch == '_';
+use unicode_xid::UnicodeXID;
+
+/// Whether `ch` can start an identifier: `_` or anything in XID_Start, per UAX #31
+pub fn is_identifier_start(ch: char) -> bool {
+    return ch == '_' || ch.is_xid_start();
+}
+
+/// Whether `ch` can follow the first character of an identifier: anything in
+/// XID_Continue, which covers `_` and digits
+pub fn is_identifier_continue(ch: char) -> bool {
+    return ch.is_xid_continue();
 }
 
+/// Only ASCII digits make up numbers, so other numeric characters like `٣`
+/// never reach the integer parser
 pub fn is_digit(ch: char) -> bool {
-    return ch.is_numeric();
-    // return ch >= '0' && ch <= '9';
+    return ch.is_ascii_digit();
 }